@@ -0,0 +1,839 @@
+use crate::catalogo::mediana;
+use crate::{Dimensiones, OpcionDeEnvio, OptimizadorError, Paquete, ServicioDeMensajeria, mejor_opcion};
+
+/// Componente del subtotal de un envío.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponenteCosto {
+    Base,
+    Peso,
+    Volumen,
+}
+
+/// Indica qué componente aporta más al subtotal del envío con un servicio.
+///
+/// Ante un empate gana el primero en el orden base, peso, volumen.
+pub fn componente_dominante(servicio: &ServicioDeMensajeria, paquete: &Paquete) -> ComponenteCosto {
+    let desglose = servicio.desglose(paquete);
+    let mut dominante = (ComponenteCosto::Base, desglose.base);
+    for candidato in [(ComponenteCosto::Peso, desglose.peso), (ComponenteCosto::Volumen, desglose.volumen)] {
+        if candidato.1 > dominante.1 {
+            dominante = candidato;
+        }
+    }
+    dominante.0
+}
+
+/// Fracciones `(base, peso, volumen)` del subtotal del envío, que suman 1.0,
+/// para graficar la composición del costo. Devuelve `None` si el subtotal es
+/// cero.
+pub fn desglose_porcentual(servicio: &ServicioDeMensajeria, paquete: &Paquete) -> Option<(f64, f64, f64)> {
+    let desglose = servicio.desglose(paquete);
+    let subtotal = desglose.subtotal();
+    if subtotal == 0.0 {
+        return None;
+    }
+    Some((desglose.base / subtotal, desglose.peso / subtotal, desglose.volumen / subtotal))
+}
+
+impl ServicioDeMensajeria {
+    /// Cuánto más cuesta enviar el paquete con un kg adicional. Con tramos de
+    /// peso, la pendiente cambia al cruzar el límite de cada tramo.
+    pub fn costo_marginal_por_kg(&self, paquete: &Paquete) -> f64 {
+        let mas_pesado = Paquete { peso_kg: paquete.peso_kg + 1.0, ..paquete.clone() };
+        self.calcular_costo(&mas_pesado) - self.calcular_costo(paquete)
+    }
+
+    /// Costo atribuible al volumen dividido por el volumen del paquete, en $
+    /// por cm³. Un paquete sin volumen devuelve 0.
+    pub fn tarifa_efectiva_por_cm3(&self, paquete: &Paquete) -> f64 {
+        let volumen = paquete.volumen_cm3();
+        if volumen <= 0.0 {
+            return 0.0;
+        }
+        self.desglose(paquete).volumen / volumen
+    }
+
+    /// Costo del paquete si pesara exactamente el `peso_maximo_kg` del
+    /// servicio, con sus dimensiones actuales, para planificar envíos que
+    /// aprovechen el límite. Devuelve `None` si el servicio no tiene límite.
+    pub fn costo_en_limite_peso(&self, paquete: &Paquete) -> Option<f64> {
+        let en_el_limite = Paquete { peso_kg: self.peso_maximo_kg?, ..paquete.clone() };
+        Some(self.calcular_costo(&en_el_limite))
+    }
+
+    /// Cuánto más cuesta enviar `b` que `a` con este servicio, es decir
+    /// `costo(b) - costo(a)`.
+    pub fn diferencia_costo(&self, a: &Paquete, b: &Paquete) -> f64 {
+        self.calcular_costo(b) - self.calcular_costo(a)
+    }
+}
+
+/// Calcula cuánto se ahorra con un servicio si cada dimensión del paquete se
+/// reduce en `reduccion_pct` por ciento, manteniendo el peso.
+///
+/// `reduccion_pct` debe estar en el intervalo abierto (0, 100).
+pub fn ahorro_por_reduccion_dimensional(
+    servicio: &ServicioDeMensajeria,
+    paquete: &Paquete,
+    reduccion_pct: f64,
+) -> Result<f64, OptimizadorError> {
+    if !(reduccion_pct > 0.0 && reduccion_pct < 100.0) {
+        return Err(OptimizadorError::ParametroInvalido(format!(
+            "reduccion_pct debe estar entre 0 y 100, se recibió {}",
+            reduccion_pct
+        )));
+    }
+
+    let factor = 1.0 - reduccion_pct / 100.0;
+    let dimensiones = &paquete.dimensiones;
+    let reducido = Paquete {
+        dimensiones: Dimensiones {
+            ancho: dimensiones.ancho * factor,
+            alto: dimensiones.alto * factor,
+            profundidad: dimensiones.profundidad * factor,
+            ..*dimensiones
+        },
+        ..paquete.clone()
+    };
+    Ok(servicio.calcular_costo(paquete) - servicio.calcular_costo(&reducido))
+}
+
+/// Elasticidad del costo respecto del volumen: el cambio porcentual del costo
+/// dividido por un cambio de `delta_pct` por ciento en el volumen del
+/// paquete, manteniendo su peso.
+///
+/// El volumen se cambia alargando el ancho. Requiere `delta_pct` distinto de
+/// cero y mayor que -100, y que el paquete original tenga costo positivo.
+pub fn elasticidad_volumen(
+    servicio: &ServicioDeMensajeria,
+    paquete: &Paquete,
+    delta_pct: f64,
+) -> Result<f64, OptimizadorError> {
+    if !(delta_pct > -100.0 && delta_pct != 0.0 && delta_pct.is_finite()) {
+        return Err(OptimizadorError::ParametroInvalido(format!(
+            "delta_pct debe ser distinto de cero y mayor que -100, se recibió {}",
+            delta_pct
+        )));
+    }
+    let costo_original = servicio.calcular_costo(paquete);
+    if costo_original <= 0.0 {
+        return Err(OptimizadorError::ParametroInvalido(
+            "el costo del paquete original debe ser positivo".to_string(),
+        ));
+    }
+
+    let mut variado = paquete.clone();
+    variado.dimensiones.ancho *= 1.0 + delta_pct / 100.0;
+    let cambio_costo_pct = (servicio.calcular_costo(&variado) - costo_original) / costo_original * 100.0;
+    Ok(cambio_costo_pct / delta_pct)
+}
+
+/// Ahorro total sobre un conjunto de paquetes si el costo base del servicio
+/// pasara a ser `nueva_base`, para modelar una negociación de contrato.
+///
+/// Se recalcula cada costo completo, ya que los recargos porcentuales también
+/// escalan el costo base. `nueva_base` no puede ser negativa.
+pub fn impacto_reduccion_base(
+    servicio: &ServicioDeMensajeria,
+    paquetes: &[Paquete],
+    nueva_base: f64,
+) -> Result<f64, OptimizadorError> {
+    if nueva_base.is_nan() || nueva_base < 0.0 {
+        return Err(OptimizadorError::ParametroInvalido(format!(
+            "nueva_base no puede ser negativa, se recibió {}",
+            nueva_base
+        )));
+    }
+
+    let mut negociado = servicio.clone();
+    negociado.tarifa.costo_base = nueva_base;
+    Ok(paquetes
+        .iter()
+        .map(|paquete| servicio.calcular_costo(paquete) - negociado.calcular_costo(paquete))
+        .sum())
+}
+
+/// Costo total de atender una región: cada paquete se cotiza a la distancia
+/// que le corresponde en `distancias` y se suman los costos.
+///
+/// Ambas listas deben tener el mismo largo.
+pub fn costo_total_region(
+    servicio: &ServicioDeMensajeria,
+    paquetes: &[Paquete],
+    distancias: &[f64],
+) -> Result<f64, OptimizadorError> {
+    if paquetes.len() != distancias.len() {
+        return Err(OptimizadorError::ParametroInvalido(format!(
+            "hay {} paquetes pero {} distancias",
+            paquetes.len(),
+            distancias.len()
+        )));
+    }
+    Ok(paquetes
+        .iter()
+        .zip(distancias)
+        .map(|(paquete, &distancia_km)| servicio.calcular_costo_con_distancia(paquete, distancia_km))
+        .sum())
+}
+
+/// Costo por paquete de un lote cuando el servicio ofrece una tarifa plana
+/// `tarifa_bulk` por todo el lote a partir de `umbral` paquetes.
+///
+/// Por debajo del umbral es el promedio de los costos individuales; desde el
+/// umbral, el menor entre ese promedio y `tarifa_bulk` dividida por la
+/// cantidad. Requiere al menos un paquete y `tarifa_bulk` no negativa.
+pub fn costo_por_paquete_bulk(
+    servicio: &ServicioDeMensajeria,
+    paquetes: &[Paquete],
+    tarifa_bulk: f64,
+    umbral: u32,
+) -> Result<f64, OptimizadorError> {
+    if paquetes.is_empty() {
+        return Err(OptimizadorError::ParametroInvalido("el lote no tiene paquetes".to_string()));
+    }
+    if tarifa_bulk.is_nan() || tarifa_bulk < 0.0 {
+        return Err(OptimizadorError::ParametroInvalido(format!(
+            "tarifa_bulk no puede ser negativa, se recibió {}",
+            tarifa_bulk
+        )));
+    }
+
+    let cantidad = paquetes.len() as f64;
+    let individual = paquetes.iter().map(|paquete| servicio.calcular_costo(paquete)).sum::<f64>() / cantidad;
+    if paquetes.len() < umbral as usize {
+        return Ok(individual);
+    }
+    Ok(individual.min(tarifa_bulk / cantidad))
+}
+
+/// Tolerancia para decidir si el último paso de una serie llega al extremo.
+const TOLERANCIA_SERIE: f64 = 1e-9;
+
+/// Diferencia máxima admitida entre la suma de probabilidades y 1.0.
+const TOLERANCIA_PROBABILIDAD: f64 = 1e-6;
+
+/// Ancho, en kg, con el que la búsqueda del peso de cruce da por cerrado el
+/// intervalo.
+const TOLERANCIA_CRUCE_KG: f64 = 1e-6;
+
+/// Busca por bisección el peso dentro de `rango` (kg) en el que los dos
+/// servicios más baratos para la caja `dims` intercambian su puesto.
+///
+/// Los dos servicios se eligen en el extremo inferior del rango, entre los
+/// que admiten el paquete en ambos extremos. Se supone que la diferencia de
+/// costo entre ambos cambia de signo a lo sumo una vez en el rango. Devuelve
+/// `None` si el rango no es válido, hay menos de dos servicios o el segundo
+/// no llega a ser más barato que el primero.
+pub fn peso_cruce_top_dos(servicios: &[ServicioDeMensajeria], dims: &Dimensiones, rango: (f64, f64)) -> Option<f64> {
+    let (desde, hasta) = rango;
+    if !(desde < hasta && desde.is_finite() && hasta.is_finite()) {
+        return None;
+    }
+    let costo = |servicio: &ServicioDeMensajeria, peso_kg| servicio.calcular_costo(&Paquete::new(peso_kg, *dims));
+
+    let mut candidatos: Vec<&ServicioDeMensajeria> = servicios
+        .iter()
+        .filter(|servicio| [desde, hasta].iter().all(|&peso_kg| servicio.admite(&Paquete::new(peso_kg, *dims))))
+        .collect();
+    candidatos.sort_by(|a, b| costo(a, desde).total_cmp(&costo(b, desde)));
+    let [primero, segundo, ..] = candidatos[..] else {
+        return None;
+    };
+
+    // Diferencia positiva: el segundo ya es más barato que el primero.
+    let diferencia = |peso_kg| costo(primero, peso_kg) - costo(segundo, peso_kg);
+    if diferencia(hasta) <= 0.0 {
+        return None;
+    }
+    let (mut bajo, mut alto) = (desde, hasta);
+    while alto - bajo > TOLERANCIA_CRUCE_KG {
+        let medio = (bajo + alto) / 2.0;
+        if diferencia(medio) > 0.0 {
+            alto = medio;
+        } else {
+            bajo = medio;
+        }
+    }
+    Some((bajo + alto) / 2.0)
+}
+
+/// Genera pares `(peso, costo)` desde `desde` hasta `hasta` kg cada `paso` kg,
+/// con las dimensiones dadas, para graficar el costo en función del peso.
+///
+/// Ambos extremos se incluyen siempre: si el rango no es múltiplo de `paso`,
+/// el último punto es `hasta`. Requiere `paso > 0` y `desde <= hasta`.
+pub fn serie_costo_peso(
+    servicio: &ServicioDeMensajeria,
+    dims: &Dimensiones,
+    desde: f64,
+    hasta: f64,
+    paso: f64,
+) -> Result<Vec<(f64, f64)>, OptimizadorError> {
+    if !(paso > 0.0 && paso.is_finite()) {
+        return Err(OptimizadorError::ParametroInvalido(format!("paso debe ser positivo, se recibió {}", paso)));
+    }
+    if !(desde <= hasta && desde.is_finite() && hasta.is_finite()) {
+        return Err(OptimizadorError::ParametroInvalido(format!(
+            "el rango de pesos [{}, {}] no es válido",
+            desde, hasta
+        )));
+    }
+
+    let pasos = ((hasta - desde) / paso + TOLERANCIA_SERIE).floor() as usize;
+    let mut pesos: Vec<f64> = (0..=pasos).map(|i| desde + i as f64 * paso).collect();
+    if hasta - pesos[pasos] > TOLERANCIA_SERIE {
+        pesos.push(hasta);
+    }
+
+    Ok(pesos
+        .into_iter()
+        .map(|peso_kg| {
+            let paquete = Paquete::new(peso_kg, *dims);
+            (peso_kg, servicio.calcular_costo(&paquete))
+        })
+        .collect())
+}
+
+/// Calcula el costo esperado con las dimensiones dadas cuando el peso sigue
+/// una distribución discreta de pares `(peso, probabilidad)`.
+///
+/// Las probabilidades no pueden ser negativas y deben sumar 1.0.
+pub fn costo_esperado(
+    servicio: &ServicioDeMensajeria,
+    dims: &Dimensiones,
+    pesos_probabilidades: &[(f64, f64)],
+) -> Result<f64, OptimizadorError> {
+    if pesos_probabilidades.iter().any(|&(_, probabilidad)| probabilidad.is_nan() || probabilidad < 0.0) {
+        return Err(OptimizadorError::ParametroInvalido(
+            "las probabilidades no pueden ser negativas".to_string(),
+        ));
+    }
+    let suma: f64 = pesos_probabilidades.iter().map(|&(_, probabilidad)| probabilidad).sum();
+    if (suma - 1.0).abs() > TOLERANCIA_PROBABILIDAD {
+        return Err(OptimizadorError::ParametroInvalido(format!(
+            "las probabilidades deben sumar 1.0, suman {}",
+            suma
+        )));
+    }
+
+    Ok(pesos_probabilidades
+        .iter()
+        .map(|&(peso_kg, probabilidad)| {
+            let paquete = Paquete::new(peso_kg, *dims);
+            servicio.calcular_costo(&paquete) * probabilidad
+        })
+        .sum())
+}
+
+/// Volumen, en cm³, con el que `a` y `b` cobran lo mismo por un paquete de
+/// `peso_kg` kg.
+///
+/// Resuelve la ecuación lineal de la tarifa: costo base más costo por peso
+/// real más `costo_por_volumen_cm3` por el volumen. No considera el peso
+/// volumétrico, el volumen incluido ni los recargos. Devuelve `None` si ambos
+/// cobran igual por cm³ o si el equilibrio cae en un volumen negativo.
+pub fn volumen_equilibrio(a: &ServicioDeMensajeria, b: &ServicioDeMensajeria, peso_kg: f64) -> Option<f64> {
+    let fijo = |servicio: &ServicioDeMensajeria| servicio.tarifa.costo_base + servicio.tarifa.costo_por_peso(peso_kg);
+    let diferencia_por_cm3 = a.tarifa.costo_por_volumen_cm3 - b.tarifa.costo_por_volumen_cm3;
+    if diferencia_por_cm3 == 0.0 {
+        return None;
+    }
+    let volumen = (fijo(b) - fijo(a)) / diferencia_por_cm3;
+    (volumen >= 0.0).then_some(volumen)
+}
+
+/// Paquete sintético representativo de un lote, para planificar con un solo
+/// paquete.
+///
+/// Cada componente es la mediana por separado: el peso mediano, el ancho
+/// mediano, el alto mediano y la profundidad mediana, con las medidas en cm.
+/// Por eso el resultado puede no coincidir con ningún paquete del lote. Con
+/// un número par de paquetes se promedian los dos valores centrales, y con
+/// una lista vacía todos los componentes valen cero.
+pub fn paquete_mediano(paquetes: &[Paquete]) -> Paquete {
+    let mediana_de =
+        |componente: fn(&Paquete) -> f64| mediana(paquetes.iter().map(componente).collect()).unwrap_or(0.0);
+    Paquete::new(
+        mediana_de(|paquete| paquete.peso_kg),
+        Dimensiones::new(
+            mediana_de(|paquete| paquete.dimensiones.en_cm().ancho),
+            mediana_de(|paquete| paquete.dimensiones.en_cm().alto),
+            mediana_de(|paquete| paquete.dimensiones.en_cm().profundidad),
+        ),
+    )
+}
+
+/// Cotiza un paquete aún sin pesar, usando su peso volumétrico con `divisor`
+/// como peso. Es una estimación por lo alto para bultos livianos y voluminosos.
+///
+/// Si la tarifa tiene su propio divisor volumétrico, el servicio sigue
+/// cobrando el mayor de ambos pesos volumétricos. Requiere `divisor > 0`.
+pub fn costo_solo_volumetrico(
+    servicio: &ServicioDeMensajeria,
+    dims: &Dimensiones,
+    divisor: f64,
+) -> Result<f64, OptimizadorError> {
+    if !(divisor > 0.0 && divisor.is_finite()) {
+        return Err(OptimizadorError::ParametroInvalido(format!(
+            "divisor debe ser positivo, se recibió {}",
+            divisor
+        )));
+    }
+
+    let paquete = Paquete::new(dims.volumen_cm3() / divisor, *dims);
+    Ok(servicio.calcular_costo(&paquete))
+}
+
+/// Cota inferior del costo: cotiza el paquete como si pudiera comprimirse,
+/// conservando su forma, hasta el volumen que equivale a su peso real con
+/// `divisor`. Así el peso volumétrico nunca supera al real.
+///
+/// Un paquete que ya es más denso se cotiza tal cual, igual que con un
+/// `divisor` no positivo o no finito.
+pub fn costo_minimo_volumetrico(servicio: &ServicioDeMensajeria, paquete: &Paquete, divisor: f64) -> f64 {
+    let volumen = paquete.volumen_cm3();
+    let volumen_minimo = paquete.peso_kg * divisor;
+    if !(divisor > 0.0 && divisor.is_finite()) || volumen <= volumen_minimo {
+        return servicio.calcular_costo(paquete);
+    }
+
+    let escala = (volumen_minimo / volumen).cbrt();
+    let mut comprimido = paquete.clone();
+    comprimido.dimensiones.ancho *= escala;
+    comprimido.dimensiones.alto *= escala;
+    comprimido.dimensiones.profundidad *= escala;
+    servicio.calcular_costo(&comprimido)
+}
+
+/// Cuánto más cuesta asegurar el envío por `valor_declarado`: la diferencia
+/// entre el costo asegurado y el normal. Un valor declarado negativo o que no
+/// es un número se toma como cero, sin seguro que cobrar.
+pub fn costo_adicional_seguro(servicio: &ServicioDeMensajeria, paquete: &Paquete, valor_declarado: f64) -> f64 {
+    let valor_declarado = valor_declarado.max(0.0);
+    servicio.calcular_costo_asegurado(paquete, valor_declarado) - servicio.calcular_costo(paquete)
+}
+
+/// Costo promedio de envío de una mezcla de productos, donde cada paquete
+/// lleva como peso su frecuencia de envío.
+///
+/// Las frecuencias no pueden ser negativas ni sumar cero; no hace falta que
+/// sumen 1.
+pub fn costo_promedio_ponderado(
+    servicio: &ServicioDeMensajeria,
+    paquetes: &[(Paquete, f64)],
+) -> Result<f64, OptimizadorError> {
+    if paquetes.iter().any(|&(_, frecuencia)| frecuencia.is_nan() || frecuencia < 0.0) {
+        return Err(OptimizadorError::ParametroInvalido(
+            "las frecuencias no pueden ser negativas".to_string(),
+        ));
+    }
+    let suma: f64 = paquetes.iter().map(|&(_, frecuencia)| frecuencia).sum();
+    if suma == 0.0 {
+        return Err(OptimizadorError::ParametroInvalido(
+            "al menos una frecuencia debe ser mayor que cero".to_string(),
+        ));
+    }
+
+    let ponderado: f64 = paquetes
+        .iter()
+        .map(|(paquete, frecuencia)| servicio.calcular_costo(paquete) * frecuencia)
+        .sum();
+    Ok(ponderado / suma)
+}
+
+/// Costo promedio del mercado para un paquete: la media del costo de los
+/// servicios que lo admiten. Devuelve `None` si ninguno lo admite.
+pub fn costo_promedio_mercado(servicios: &[ServicioDeMensajeria], paquete: &Paquete) -> Option<f64> {
+    let costos: Vec<f64> = servicios
+        .iter()
+        .filter(|servicio| servicio.admite(paquete))
+        .map(|servicio| servicio.calcular_costo(paquete))
+        .collect();
+    if costos.is_empty() {
+        return None;
+    }
+    Some(costos.iter().sum::<f64>() / costos.len() as f64)
+}
+
+/// Devuelve la opción más barata y cuánto por ciento está por debajo del
+/// costo promedio del mercado. Si el promedio es cero, el porcentaje es cero.
+pub fn descuento_vs_promedio<'a>(
+    servicios: &'a [ServicioDeMensajeria],
+    paquete: &Paquete,
+) -> Option<(OpcionDeEnvio<'a>, f64)> {
+    let promedio = costo_promedio_mercado(servicios, paquete)?;
+    let mas_barata = mejor_opcion(servicios, paquete)?;
+    let porcentaje = if promedio > 0.0 { (promedio - mas_barata.costo) / promedio * 100.0 } else { 0.0 };
+    Some((mas_barata, porcentaje))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Tarifa, TramoPeso};
+
+    #[test]
+    fn test_ahorro_reduccion_20_pct_paquete_voluminoso() {
+        let servicio = ServicioDeMensajeria {
+            nombre: "Volumétrico".to_string(),
+            tarifa: Tarifa { costo_base: 2.0, costo_por_kg: 0.5, costo_por_volumen_cm3: 0.001, ..Default::default() },
+            ..Default::default()
+        };
+        let paquete = Paquete::new(1.0, Dimensiones::new(50.0, 40.0, 30.0));
+
+        // 60000 cm³ -> 60000 * 0.8³ = 30720 cm³: se ahorran 29280 cm³ * 0.001.
+        let ahorro = ahorro_por_reduccion_dimensional(&servicio, &paquete, 20.0).unwrap();
+        assert!((ahorro - 29.28).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ahorro_reduccion_rechaza_porcentaje_fuera_de_rango() {
+        let servicio = ServicioDeMensajeria::default();
+        let paquete = Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0));
+        for pct in [0.0, 100.0, -5.0, f64::NAN] {
+            assert!(matches!(
+                ahorro_por_reduccion_dimensional(&servicio, &paquete, pct),
+                Err(OptimizadorError::ParametroInvalido(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_serie_costo_peso() {
+        let servicio = ServicioDeMensajeria {
+            nombre: "Lineal".to_string(),
+            tarifa: Tarifa { costo_base: 5.0, costo_por_kg: 2.0, ..Default::default() },
+            ..Default::default()
+        };
+        let dims = Dimensiones::new(10.0, 10.0, 10.0);
+
+        let serie = serie_costo_peso(&servicio, &dims, 1.0, 3.0, 0.1).unwrap();
+        assert_eq!(serie.len(), 21);
+        assert_eq!(serie[0], (1.0, 7.0));
+        let (peso_final, costo_final) = serie[20];
+        assert!((peso_final - 3.0).abs() < 1e-9);
+        assert!((costo_final - 11.0).abs() < 1e-9);
+
+        // Un rango que no es múltiplo del paso termina igualmente en `hasta`.
+        let serie = serie_costo_peso(&servicio, &dims, 0.0, 1.0, 0.3).unwrap();
+        assert_eq!(serie.len(), 5);
+        assert_eq!(serie[4], (1.0, 7.0));
+    }
+
+    #[test]
+    fn test_serie_costo_peso_valida_parametros() {
+        let servicio = ServicioDeMensajeria::default();
+        let dims = Dimensiones::new(1.0, 1.0, 1.0);
+        assert!(serie_costo_peso(&servicio, &dims, 0.0, 1.0, 0.0).is_err());
+        assert!(serie_costo_peso(&servicio, &dims, 2.0, 1.0, 0.5).is_err());
+    }
+
+    #[test]
+    fn test_costo_esperado_distribucion_de_dos_puntos() {
+        let servicio = ServicioDeMensajeria {
+            nombre: "Lineal".to_string(),
+            tarifa: Tarifa { costo_base: 5.0, costo_por_kg: 2.0, ..Default::default() },
+            ..Default::default()
+        };
+        let dims = Dimensiones::new(10.0, 10.0, 10.0);
+
+        // 0.25 * (5 + 2*2) + 0.75 * (5 + 2*10) = 2.25 + 18.75
+        let esperado = costo_esperado(&servicio, &dims, &[(2.0, 0.25), (10.0, 0.75)]).unwrap();
+        assert!((esperado - 21.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_costo_esperado_valida_probabilidades() {
+        let servicio = ServicioDeMensajeria::default();
+        let dims = Dimensiones::new(1.0, 1.0, 1.0);
+        assert!(costo_esperado(&servicio, &dims, &[(1.0, 0.5), (2.0, 0.4)]).is_err());
+        assert!(costo_esperado(&servicio, &dims, &[(1.0, 1.5), (2.0, -0.5)]).is_err());
+    }
+
+    fn servicio_con_tarifa(costo_base: f64, costo_por_kg: f64, costo_por_volumen_cm3: f64) -> ServicioDeMensajeria {
+        ServicioDeMensajeria {
+            nombre: "Componentes".to_string(),
+            tarifa: Tarifa { costo_base, costo_por_kg, costo_por_volumen_cm3, ..Default::default() },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_componente_dominante() {
+        // 5 kg y 1000 cm³.
+        let paquete = Paquete::new(5.0, Dimensiones::new(10.0, 10.0, 10.0));
+        assert_eq!(componente_dominante(&servicio_con_tarifa(30.0, 1.0, 0.001), &paquete), ComponenteCosto::Base);
+        assert_eq!(componente_dominante(&servicio_con_tarifa(2.0, 3.0, 0.001), &paquete), ComponenteCosto::Peso);
+        assert_eq!(componente_dominante(&servicio_con_tarifa(2.0, 1.0, 0.02), &paquete), ComponenteCosto::Volumen);
+    }
+
+    #[test]
+    fn test_costo_marginal_cambia_al_cruzar_tramo() {
+        let servicio = ServicioDeMensajeria {
+            nombre: "Tramos".to_string(),
+            tarifa: Tarifa {
+                costo_base: 4.0,
+                costo_por_kg: 1.0,
+                tramos_peso: vec![TramoPeso { hasta_kg: 10.0, costo_por_kg: 2.0 }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let paquete = |peso_kg| Paquete::new(peso_kg, Dimensiones::new(10.0, 10.0, 10.0));
+
+        assert_eq!(servicio.costo_marginal_por_kg(&paquete(5.0)), 2.0);
+        // Medio kg se cobra en el primer tramo y medio en el siguiente.
+        assert_eq!(servicio.costo_marginal_por_kg(&paquete(9.5)), 1.5);
+        assert_eq!(servicio.costo_marginal_por_kg(&paquete(12.0)), 1.0);
+        assert_eq!(servicio.calcular_costo(&paquete(12.0)), 4.0 + 20.0 + 2.0);
+    }
+
+    #[test]
+    fn test_diferencia_costo_caja_mas_grande() {
+        let servicio = servicio_con_tarifa(5.0, 1.0, 0.001);
+        let pequena = Paquete::new(2.0, Dimensiones::new(10.0, 10.0, 10.0));
+        let grande = Paquete::new(3.0, Dimensiones::new(20.0, 10.0, 10.0));
+
+        // Un kg más (1.0) y 1000 cm³ más (1.0).
+        let diferencia = servicio.diferencia_costo(&pequena, &grande);
+        assert!(diferencia > 0.0);
+        assert!((diferencia - 2.0).abs() < 1e-9);
+        assert!((servicio.diferencia_costo(&grande, &pequena) + 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_costo_solo_volumetrico_coincide_si_domina_el_volumen() {
+        let servicio = ServicioDeMensajeria {
+            tarifa: Tarifa { costo_base: 3.0, costo_por_kg: 2.0, divisor_volumetrico: Some(5000.0), ..Default::default() },
+            ..Default::default()
+        };
+        // 64000 cm³ / 5000 = 12.8 kg volumétricos, muy por encima de 1 kg real.
+        let dims = Dimensiones::new(40.0, 40.0, 40.0);
+        let paquete = Paquete::new(1.0, dims);
+
+        let estimado = costo_solo_volumetrico(&servicio, &dims, 5000.0).unwrap();
+        assert_eq!(estimado, servicio.calcular_costo(&paquete));
+        assert!(costo_solo_volumetrico(&servicio, &dims, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_costo_minimo_volumetrico_de_un_bulto_voluminoso() {
+        let servicio = ServicioDeMensajeria {
+            tarifa: Tarifa {
+                costo_base: 3.0,
+                costo_por_kg: 2.0,
+                costo_por_volumen_cm3: 0.0001,
+                divisor_volumetrico: Some(5000.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        // 64000 cm³ frente a 2 kg · 5000 = 10000 cm³ comprimidos.
+        let paquete = Paquete::new(2.0, Dimensiones::new(40.0, 40.0, 40.0));
+
+        let minimo = costo_minimo_volumetrico(&servicio, &paquete, 5000.0);
+        assert!((minimo - (3.0 + 2.0 * 2.0 + 10000.0 * 0.0001)).abs() < 1e-9);
+        assert!(minimo < servicio.calcular_costo(&paquete));
+
+        let denso = Paquete::new(20.0, Dimensiones::new(10.0, 10.0, 10.0));
+        assert_eq!(costo_minimo_volumetrico(&servicio, &denso, 5000.0), servicio.calcular_costo(&denso));
+    }
+
+    #[test]
+    fn test_peso_cruce_top_dos() {
+        // 5 + 2p y 10 + p se cruzan en 5 kg; el tercero nunca está entre los dos primeros.
+        let servicios = vec![
+            servicio_con_tarifa(20.0, 3.0, 0.0),
+            servicio_con_tarifa(5.0, 2.0, 0.0),
+            servicio_con_tarifa(10.0, 1.0, 0.0),
+        ];
+        let dims = Dimensiones::new(10.0, 10.0, 10.0);
+
+        let cruce = peso_cruce_top_dos(&servicios, &dims, (0.5, 20.0)).unwrap();
+        assert!((cruce - 5.0).abs() < 1e-5);
+        assert_eq!(peso_cruce_top_dos(&servicios, &dims, (0.5, 4.0)), None);
+        assert_eq!(peso_cruce_top_dos(&servicios[..1], &dims, (0.5, 20.0)), None);
+    }
+
+    #[test]
+    fn test_volumen_equilibrio_con_cruce() {
+        // 4 + 2 + 0.002 V = 10 + 1 + 0.0005 V en V = 5 / 0.0015.
+        let a = servicio_con_tarifa(4.0, 1.0, 0.002);
+        let b = servicio_con_tarifa(10.0, 0.5, 0.0005);
+
+        let volumen = volumen_equilibrio(&a, &b, 2.0).unwrap();
+        assert!((volumen - 5.0 / 0.0015).abs() < 1e-6);
+        let lado = volumen.cbrt();
+        let paquete = Paquete::new(2.0, Dimensiones::new(lado, lado, lado));
+        assert!((a.calcular_costo(&paquete) - b.calcular_costo(&paquete)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_volumen_equilibrio_tarifas_paralelas() {
+        let a = servicio_con_tarifa(4.0, 1.0, 0.001);
+        let b = servicio_con_tarifa(10.0, 0.5, 0.001);
+        assert_eq!(volumen_equilibrio(&a, &b, 2.0), None);
+    }
+
+    #[test]
+    fn test_paquete_mediano_componente_a_componente() {
+        let paquetes = [
+            (2.0, 30.0, 10.0, 20.0),
+            (8.0, 10.0, 40.0, 20.0),
+            (1.0, 20.0, 20.0, 60.0),
+            (5.0, 50.0, 30.0, 10.0),
+            (3.0, 40.0, 50.0, 30.0),
+        ]
+        .map(|(peso_kg, ancho, alto, profundidad)| Paquete::new(peso_kg, Dimensiones::new(ancho, alto, profundidad)));
+
+        let mediano = paquete_mediano(&paquetes);
+        assert_eq!(mediano.peso_kg, 3.0);
+        assert_eq!(mediano.dimensiones, Dimensiones::new(30.0, 30.0, 20.0));
+        assert_eq!(paquete_mediano(&[]).peso_kg, 0.0);
+    }
+
+    #[test]
+    fn test_costo_promedio_ponderado() {
+        let servicio = servicio_con_tarifa(2.0, 1.0, 0.0);
+        let dims = Dimensiones::new(10.0, 10.0, 10.0);
+        // Costos 3 y 7, con el primero enviado tres veces más seguido.
+        let mezcla = [
+            (Paquete::new(1.0, dims), 3.0),
+            (Paquete::new(5.0, dims), 1.0),
+        ];
+        assert_eq!(costo_promedio_ponderado(&servicio, &mezcla).unwrap(), 4.0);
+
+        let sin_frecuencia = [(Paquete::new(1.0, dims), 0.0)];
+        assert!(costo_promedio_ponderado(&servicio, &sin_frecuencia).is_err());
+        let negativa = [(Paquete::new(1.0, dims), -1.0)];
+        assert!(costo_promedio_ponderado(&servicio, &negativa).is_err());
+    }
+
+    #[test]
+    fn test_costo_adicional_seguro() {
+        let servicio = ServicioDeMensajeria {
+            tarifa: Tarifa { costo_base: 5.0, tasa_seguro_pct: Some(1.5), ..Default::default() },
+            ..Default::default()
+        };
+        let paquete = Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0));
+
+        let adicional = costo_adicional_seguro(&servicio, &paquete, 200.0);
+        assert!((adicional - 200.0 * 1.5 / 100.0).abs() < 1e-9);
+        assert_eq!(costo_adicional_seguro(&servicio, &paquete, -1.0), 0.0);
+        assert_eq!(costo_adicional_seguro(&servicio, &paquete, f64::NAN), 0.0);
+    }
+
+    #[test]
+    fn test_tarifa_efectiva_por_cm3() {
+        let servicio = servicio_con_tarifa(5.0, 1.5, 0.002);
+        let paquete = Paquete::new(3.0, Dimensiones::new(15.0, 10.0, 20.0));
+        assert!((servicio.tarifa_efectiva_por_cm3(&paquete) - 0.002).abs() < 1e-12);
+
+        let sobre = Paquete::new(0.1, Dimensiones::new(30.0, 20.0, 0.0));
+        assert_eq!(servicio.tarifa_efectiva_por_cm3(&sobre), 0.0);
+    }
+
+    #[test]
+    fn test_costo_promedio_mercado() {
+        // Catálogo de ejemplo de `main`: cuesta 16.25, 17.0 y 31.5.
+        let servicios = vec![
+            servicio_con_tarifa(5.0, 1.5, 0.001),
+            servicio_con_tarifa(8.0, 1.2, 0.0008),
+            servicio_con_tarifa(20.0, 1.0, 0.002),
+        ];
+        let paquete = Paquete::new(5.5, Dimensiones::new(15.0, 10.0, 20.0));
+
+        let promedio = costo_promedio_mercado(&servicios, &paquete).unwrap();
+        assert!((promedio - (16.25 + 17.0 + 31.5) / 3.0).abs() < 1e-9);
+        assert_eq!(costo_promedio_mercado(&[], &paquete), None);
+    }
+
+    #[test]
+    fn test_descuento_vs_promedio() {
+        // Costos 6, 9 y 15: promedio 10, la más barata está 40 % por debajo.
+        let servicios = vec![
+            servicio_con_tarifa(6.0, 0.0, 0.0),
+            servicio_con_tarifa(9.0, 0.0, 0.0),
+            servicio_con_tarifa(15.0, 0.0, 0.0),
+        ];
+        let paquete = Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0));
+
+        let (opcion, porcentaje) = descuento_vs_promedio(&servicios, &paquete).unwrap();
+        assert_eq!(opcion.costo, 6.0);
+        assert!((porcentaje - 40.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_elasticidad_volumen_cercana_a_uno_si_domina_el_volumen() {
+        let servicio = servicio_con_tarifa(0.1, 0.01, 0.01);
+        let paquete = Paquete::new(1.0, Dimensiones::new(20.0, 20.0, 20.0));
+
+        let elasticidad = elasticidad_volumen(&servicio, &paquete, 10.0).unwrap();
+        assert!((elasticidad - 1.0).abs() < 0.01);
+        assert!(elasticidad_volumen(&servicio, &paquete, 0.0).is_err());
+        assert!(elasticidad_volumen(&ServicioDeMensajeria::default(), &paquete, 10.0).is_err());
+    }
+
+    #[test]
+    fn test_impacto_reduccion_base() {
+        let servicio = servicio_con_tarifa(20.0, 1.0, 0.002);
+        let dims = Dimensiones::new(15.0, 10.0, 20.0);
+        let paquetes = [Paquete::new(1.0, dims), Paquete::new(5.5, dims), Paquete::new(12.0, dims)];
+
+        // Bajar la base de 20 a 12.5 ahorra 7.5 por paquete.
+        let ahorro = impacto_reduccion_base(&servicio, &paquetes, 12.5).unwrap();
+        assert!((ahorro - 22.5).abs() < 1e-9);
+        assert!(impacto_reduccion_base(&servicio, &paquetes, -1.0).is_err());
+    }
+
+    #[test]
+    fn test_desglose_porcentual_suma_uno() {
+        let paquete = Paquete::new(5.5, Dimensiones::new(15.0, 10.0, 20.0));
+        let (base, peso, volumen) = desglose_porcentual(&servicio_con_tarifa(8.0, 1.2, 0.0008), &paquete).unwrap();
+        assert!((base + peso + volumen - 1.0).abs() < 1e-9);
+        assert!((base - 8.0 / 17.0).abs() < 1e-9);
+
+        assert_eq!(desglose_porcentual(&ServicioDeMensajeria::default(), &paquete), None);
+    }
+
+    #[test]
+    fn test_costo_en_limite_peso() {
+        let mut servicio = servicio_con_tarifa(5.0, 1.5, 0.001);
+        let paquete = Paquete::new(2.0, Dimensiones::new(15.0, 10.0, 20.0));
+        assert_eq!(servicio.costo_en_limite_peso(&paquete), None);
+
+        servicio.peso_maximo_kg = Some(30.0);
+        let costo = servicio.costo_en_limite_peso(&paquete).unwrap();
+        assert!((costo - (5.0 + 30.0 * 1.5 + 3000.0 * 0.001)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_costo_total_region() {
+        let mut servicio = servicio_con_tarifa(2.0, 1.0, 0.0);
+        servicio.tarifa.costo_por_km = 0.5;
+        let dims = Dimensiones::new(10.0, 10.0, 10.0);
+        let paquetes = [Paquete::new(1.0, dims), Paquete::new(3.0, dims)];
+
+        // (2 + 1 + 0.5 * 10) + (2 + 3 + 0.5 * 40).
+        assert_eq!(costo_total_region(&servicio, &paquetes, &[10.0, 40.0]), Ok(33.0));
+        assert!(costo_total_region(&servicio, &paquetes, &[10.0]).is_err());
+    }
+
+    #[test]
+    fn test_costo_por_paquete_bulk_bajo_el_umbral() {
+        let servicio = servicio_con_tarifa(5.0, 1.0, 0.0);
+        let paquetes = vec![Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0)); 4];
+        // Cuatro paquetes de 6 no alcanzan el umbral de 5: no aplica la tarifa plana de 10.
+        assert_eq!(costo_por_paquete_bulk(&servicio, &paquetes, 10.0, 5), Ok(6.0));
+    }
+
+    #[test]
+    fn test_costo_por_paquete_bulk_sobre_el_umbral() {
+        let servicio = servicio_con_tarifa(5.0, 1.0, 0.0);
+        let paquetes = vec![Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0)); 5];
+        assert_eq!(costo_por_paquete_bulk(&servicio, &paquetes, 20.0, 5), Ok(4.0));
+        // Si la tarifa plana sale más cara, se mantiene el costo individual.
+        assert_eq!(costo_por_paquete_bulk(&servicio, &paquetes, 50.0, 5), Ok(6.0));
+        assert!(costo_por_paquete_bulk(&servicio, &[], 20.0, 5).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+use crate::{OpcionDeEnvio, Paquete, ServicioDeMensajeria, Tarifa};
+
+impl ServicioDeMensajeria {
+    /// Calcula el costo de envío en una fecha, aplicando el descuento
+    /// promocional sólo si la fecha cae dentro de su vigencia. El piso global
+    /// se aplica después del descuento.
+    pub fn calcular_costo_en_fecha(&self, paquete: &Paquete, fecha: NaiveDate) -> f64 {
+        let costo = self.costo_sin_piso(paquete, true);
+        let con_descuento = match self.descuento_vigente {
+            Some((desde, hasta, porcentaje)) if (desde..=hasta).contains(&fecha) => {
+                costo * (1.0 - porcentaje / 100.0)
+            }
+            _ => costo,
+        };
+        self.aplicar_piso(con_descuento)
+    }
+
+    /// Indica si el servicio recoge envíos en la fecha dada.
+    pub fn opera_en(&self, fecha: NaiveDate) -> bool {
+        self.dias_operacion.is_empty() || self.dias_operacion.contains(&fecha.weekday())
+    }
+
+    /// Estima la fecha de entrega sumando a `fecha_envio` los días hábiles del
+    /// servicio, sin contar sábados ni domingos. Devuelve `None` si el
+    /// servicio no tiene `dias_estimados`.
+    pub fn fecha_entrega_estimada(&self, fecha_envio: NaiveDate) -> Option<NaiveDate> {
+        let mut fecha = fecha_envio;
+        let mut restantes = self.dias_estimados?;
+        while restantes > 0 {
+            fecha = fecha.checked_add_days(Days::new(1))?;
+            if !matches!(fecha.weekday(), Weekday::Sat | Weekday::Sun) {
+                restantes -= 1;
+            }
+        }
+        Some(fecha)
+    }
+}
+
+/// Encuentra la opción más barata para despachar el paquete en `fecha_envio`,
+/// descartando los servicios que no operan ese día o no admiten el paquete.
+/// Los costos incluyen los descuentos vigentes en esa fecha.
+pub fn mas_barata_en_fecha<'a>(
+    servicios: &'a [ServicioDeMensajeria],
+    paquete: &Paquete,
+    fecha_envio: NaiveDate,
+) -> Option<OpcionDeEnvio<'a>> {
+    let mut mejor_opcion: Option<OpcionDeEnvio<'a>> = None;
+
+    for servicio in servicios.iter().filter(|servicio| servicio.opera_en(fecha_envio) && servicio.admite(paquete)) {
+        let costo_actual = servicio.calcular_costo_en_fecha(paquete, fecha_envio);
+        if mejor_opcion.as_ref().is_none_or(|mejor| costo_actual < mejor.costo) {
+            mejor_opcion = Some(OpcionDeEnvio {
+                servicio: &servicio.nombre,
+                costo: costo_actual,
+            });
+        }
+    }
+    mejor_opcion
+}
+
+/// Proyecta el costo del paquete bajo cada cambio de tarifa anunciado,
+/// ordenado por fecha de entrada en vigencia.
+///
+/// Cada tarifa reemplaza a la del servicio; el resto de su configuración
+/// (redondeo, piso global, límites) se conserva.
+pub fn proyectar_costos(
+    servicio: &ServicioDeMensajeria,
+    paquete: &Paquete,
+    cambios: &[(NaiveDate, Tarifa)],
+) -> Vec<(NaiveDate, f64)> {
+    let mut proyeccion: Vec<(NaiveDate, f64)> = cambios
+        .iter()
+        .map(|(fecha, tarifa)| {
+            let con_tarifa = ServicioDeMensajeria { tarifa: tarifa.clone(), ..servicio.clone() };
+            (*fecha, con_tarifa.calcular_costo(paquete))
+        })
+        .collect();
+    proyeccion.sort_by_key(|&(fecha, _)| fecha);
+    proyeccion
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dimensiones;
+
+    fn fecha(anio: i32, mes: u32, dia: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(anio, mes, dia).unwrap()
+    }
+
+    fn servicio_con_promocion() -> ServicioDeMensajeria {
+        ServicioDeMensajeria {
+            nombre: "Promo Courier".to_string(),
+            tarifa: Tarifa { costo_base: 10.0, costo_por_kg: 2.0, costo_por_volumen_cm3: 0.0, ..Default::default() },
+            descuento_vigente: Some((fecha(2024, 11, 25), fecha(2024, 12, 2), 25.0)),
+            ..Default::default()
+        }
+    }
+
+    fn paquete() -> Paquete {
+        Paquete::new(5.0, Dimensiones::new(10.0, 10.0, 10.0))
+    }
+
+    #[test]
+    fn test_descuento_dentro_de_vigencia() {
+        let servicio = servicio_con_promocion();
+        assert_eq!(servicio.calcular_costo_en_fecha(&paquete(), fecha(2024, 11, 29)), 15.0);
+        assert_eq!(servicio.calcular_costo_en_fecha(&paquete(), fecha(2024, 12, 2)), 15.0);
+    }
+
+    #[test]
+    fn test_descuento_fuera_de_vigencia() {
+        let servicio = servicio_con_promocion();
+        assert_eq!(servicio.calcular_costo_en_fecha(&paquete(), fecha(2024, 12, 3)), 20.0);
+    }
+
+    #[test]
+    fn test_entrega_desde_viernes_salta_fin_de_semana() {
+        let servicio = ServicioDeMensajeria { dias_estimados: Some(2), ..Default::default() };
+        // 2024-11-29 es viernes; dos días hábiles después es el martes 3 de diciembre.
+        assert_eq!(servicio.fecha_entrega_estimada(fecha(2024, 11, 29)), Some(fecha(2024, 12, 3)));
+    }
+
+    #[test]
+    fn test_servicio_sin_fines_de_semana_se_excluye_el_domingo() {
+        let dias_habiles = vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
+        let servicios = vec![
+            ServicioDeMensajeria {
+                nombre: "Oficina".to_string(),
+                tarifa: Tarifa { costo_base: 5.0, ..Default::default() },
+                dias_operacion: dias_habiles,
+                ..Default::default()
+            },
+            ServicioDeMensajeria {
+                nombre: "Siempre abierto".to_string(),
+                tarifa: Tarifa { costo_base: 9.0, ..Default::default() },
+                ..Default::default()
+            },
+        ];
+
+        // 2024-12-01 es domingo y 2024-12-02, lunes.
+        let domingo = mas_barata_en_fecha(&servicios, &paquete(), fecha(2024, 12, 1)).unwrap();
+        assert_eq!(domingo.servicio, "Siempre abierto");
+        let lunes = mas_barata_en_fecha(&servicios, &paquete(), fecha(2024, 12, 2)).unwrap();
+        assert_eq!(lunes.servicio, "Oficina");
+    }
+
+    #[test]
+    fn test_proyectar_costos_con_dos_cambios() {
+        let servicio = servicio_con_promocion();
+        let cambios = vec![
+            (fecha(2025, 7, 1), Tarifa { costo_base: 14.0, costo_por_kg: 2.5, ..Default::default() }),
+            (fecha(2025, 1, 1), Tarifa { costo_base: 12.0, costo_por_kg: 2.0, ..Default::default() }),
+        ];
+
+        // Paquete de 5 kg: 12 + 2 * 5 y 14 + 2.5 * 5.
+        assert_eq!(
+            proyectar_costos(&servicio, &paquete(), &cambios),
+            vec![(fecha(2025, 1, 1), 22.0), (fecha(2025, 7, 1), 26.5)]
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    CargaError, Dimensiones, FilaInvalida, OptimizadorError, Paquete, SCHEMA_VERSION, ServicioDeMensajeria, TablaZonas, mejor_opcion,
+};
+
+/// Encabezado de las columnas de un CSV de paquetes.
+const ENCABEZADO_CSV_PAQUETES: &str = "peso,ancho,alto,profundidad";
+
+/// Catálogo de servicios con la versión del formato en que se guardó.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Catalogo {
+    pub schema_version: u32,
+    pub servicios: Vec<ServicioDeMensajeria>,
+}
+
+impl Catalogo {
+    /// Crea un catálogo con la versión de formato actual.
+    pub fn new(servicios: Vec<ServicioDeMensajeria>) -> Self {
+        Catalogo { schema_version: SCHEMA_VERSION, servicios }
+    }
+}
+
+/// Sólo la versión del catálogo, para revisarla antes de leer el resto.
+#[derive(Deserialize)]
+struct EncabezadoCatalogo {
+    schema_version: u32,
+}
+
+/// Lee un único paquete en formato JSON desde cualquier lector.
+pub fn leer_paquete(lector: impl Read) -> Result<Paquete, CargaError> {
+    let contenido = leer_contenido(lector)?;
+    Ok(serde_json::from_str(&contenido)?)
+}
+
+/// Lee un único paquete en formato JSON desde la entrada estándar, p. ej.
+/// `echo '{"peso_kg": 2.0, ...}' | optimizador_envios`.
+pub fn leer_paquete_stdin() -> Result<Paquete, CargaError> {
+    leer_paquete(io::stdin().lock())
+}
+
+/// Lee un catálogo en formato JSON desde cualquier lector.
+///
+/// Rechaza con `CargaError::VersionNoSoportada` los catálogos de una versión
+/// de formato posterior a `SCHEMA_VERSION`, antes de intentar interpretar sus
+/// servicios.
+pub fn leer_catalogo(lector: impl Read) -> Result<Catalogo, CargaError> {
+    let contenido = leer_contenido(lector)?;
+    let encabezado: EncabezadoCatalogo = serde_json::from_str(&contenido)?;
+    if encabezado.schema_version > SCHEMA_VERSION {
+        return Err(CargaError::VersionNoSoportada {
+            encontrada: encabezado.schema_version,
+            soportada: SCHEMA_VERSION,
+        });
+    }
+    Ok(serde_json::from_str(&contenido)?)
+}
+
+/// Lee una tabla de recargos por zona en formato JSON, p. ej.
+/// `{"factores": {"rural": 1.25}, "factor_por_defecto": 1.0}`.
+pub fn leer_tabla_zonas(lector: impl Read) -> Result<TablaZonas, CargaError> {
+    let contenido = leer_contenido(lector)?;
+    Ok(serde_json::from_str(&contenido)?)
+}
+
+/// Lee toda la entrada, rechazando la que no trae datos.
+fn leer_contenido(mut lector: impl Read) -> Result<String, CargaError> {
+    let mut contenido = String::new();
+    lector.read_to_string(&mut contenido)?;
+    if contenido.trim().is_empty() {
+        return Err(CargaError::EntradaVacia);
+    }
+    Ok(contenido)
+}
+
+/// Cotiza un CSV de paquetes con columnas `peso,ancho,alto,profundidad` (kg y
+/// cm) y escribe otro CSV con esas columnas más el servicio más barato y su
+/// costo.
+///
+/// El encabezado de la entrada es opcional y las líneas en blanco se omiten.
+/// Los paquetes que ningún servicio admite se escriben con servicio y costo
+/// vacíos.
+///
+/// Las filas mal formadas, o con un peso o una medida que no es un número
+/// positivo y finito, no se escriben y no detienen el proceso: al terminar se
+/// devuelven todas juntas en `FilasInvalidas`, con su número de línea. Las
+/// filas válidas quedan escritas en `salida` en cualquier caso.
+pub fn procesar_csv_a_csv(
+    servicios: &[ServicioDeMensajeria],
+    entrada: impl Read,
+    mut salida: impl Write,
+) -> Result<(), OptimizadorError> {
+    let mut invalidas = Vec::new();
+    writeln!(salida, "{},servicio,costo", ENCABEZADO_CSV_PAQUETES)?;
+    for (indice, linea) in BufReader::new(entrada).lines().enumerate() {
+        let linea = linea?;
+        let fila = linea.trim();
+        if fila.is_empty() || (indice == 0 && fila == ENCABEZADO_CSV_PAQUETES) {
+            continue;
+        }
+        let paquete = match paquete_desde_fila(fila) {
+            Ok(paquete) => paquete,
+            Err(motivo) => {
+                invalidas.push(FilaInvalida { linea: indice + 1, motivo });
+                continue;
+            }
+        };
+        match mejor_opcion(servicios, &paquete) {
+            Some(opcion) => writeln!(salida, "{},{},{:.2}", fila, campo_csv(opcion.servicio), opcion.costo)?,
+            None => writeln!(salida, "{},,", fila)?,
+        }
+    }
+    if invalidas.is_empty() { Ok(()) } else { Err(OptimizadorError::FilasInvalidas(invalidas)) }
+}
+
+/// Interpreta una fila `peso,ancho,alto,profundidad`.
+fn paquete_desde_fila(fila: &str) -> Result<Paquete, String> {
+    let valores = fila
+        .split(',')
+        .map(|campo| {
+            campo
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("'{}' no es un número", campo.trim()))
+        })
+        .collect::<Result<Vec<f64>, String>>()?;
+    let [peso_kg, ancho, alto, profundidad] = valores[..] else {
+        return Err(format!("se esperaban 4 columnas, hay {}", valores.len()));
+    };
+    for (columna, valor) in ["peso", "ancho", "alto", "profundidad"].into_iter().zip(&valores) {
+        if !(*valor > 0.0 && valor.is_finite()) {
+            return Err(format!("{} debe ser positivo, se recibió {}", columna, valor));
+        }
+    }
+    Ok(Paquete::new(peso_kg, Dimensiones::new(ancho, alto, profundidad)))
+}
+
+/// Entrecomilla un campo de texto si contiene comas o comillas.
+fn campo_csv(texto: &str) -> String {
+    if texto.contains([',', '"']) {
+        format!("\"{}\"", texto.replace('"', "\"\""))
+    } else {
+        texto.to_string()
+    }
+}
+
+/// Serializa un catálogo a JSON.
+pub fn catalogo_a_json(catalogo: &Catalogo) -> serde_json::Result<String> {
+    serde_json::to_string(catalogo)
+}
+
+/// Serializa un servicio a JSON.
+pub fn servicio_a_json(servicio: &ServicioDeMensajeria) -> serde_json::Result<String> {
+    serde_json::to_string(servicio)
+}
+
+/// Reconstruye un servicio desde su representación JSON.
+pub fn servicio_desde_json(json: &str) -> serde_json::Result<ServicioDeMensajeria> {
+    serde_json::from_str(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dimensiones, MetadatosServicio, Moneda, Recargo, Tarifa, TramoPeso};
+
+    #[test]
+    fn test_leer_paquete_json() {
+        let json = br#"{"peso_kg": 2.5, "dimensiones": {"ancho": 10.0, "alto": 20.0, "profundidad": 30.0}}"#;
+        let paquete = leer_paquete(&json[..]).unwrap();
+        assert_eq!(paquete.peso_kg, 2.5);
+        assert_eq!(paquete.volumen_cm3(), 6000.0);
+        assert_eq!(paquete.id, None);
+    }
+
+    #[test]
+    fn test_leer_paquete_con_id() {
+        let json = br#"{"id": "PED-42", "peso_kg": 1.0, "dimensiones": {"ancho": 1.0, "alto": 1.0, "profundidad": 1.0}}"#;
+        assert_eq!(leer_paquete(&json[..]).unwrap().id.as_deref(), Some("PED-42"));
+    }
+
+    #[test]
+    fn test_leer_paquete_entrada_vacia() {
+        assert!(matches!(leer_paquete(&b"  \n"[..]), Err(CargaError::EntradaVacia)));
+    }
+
+    #[test]
+    fn test_leer_paquete_json_malformado() {
+        assert!(matches!(leer_paquete(&b"{\"peso_kg\": "[..]), Err(CargaError::JsonInvalido(_))));
+    }
+
+    fn servicios_csv() -> Vec<ServicioDeMensajeria> {
+        vec![
+            ServicioDeMensajeria {
+                nombre: "Moto".to_string(),
+                tarifa: Tarifa { costo_base: 3.0, costo_por_kg: 2.0, ..Default::default() },
+                peso_maximo_kg: Some(5.0),
+                ..Default::default()
+            },
+            ServicioDeMensajeria {
+                nombre: "Camión, carga".to_string(),
+                tarifa: Tarifa { costo_base: 10.0, costo_por_kg: 0.5, ..Default::default() },
+                peso_maximo_kg: Some(50.0),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_procesar_csv_a_csv() {
+        let entrada = "peso,ancho,alto,profundidad\n1.5,10,10,10\n\n20,40,30,30\n80,50,50,50\n";
+        let mut salida = Vec::new();
+        procesar_csv_a_csv(&servicios_csv(), entrada.as_bytes(), &mut salida).unwrap();
+
+        assert_eq!(
+            String::from_utf8(salida).unwrap(),
+            "peso,ancho,alto,profundidad,servicio,costo\n\
+             1.5,10,10,10,Moto,6.00\n\
+             20,40,30,30,\"Camión, carga\",20.00\n\
+             80,50,50,50,,\n"
+        );
+    }
+
+    #[test]
+    fn test_procesar_csv_informa_todas_las_lineas_invalidas() {
+        let entrada = "peso,ancho,alto,profundidad\n2,10,diez,10\n1,10,10,10\n1,10,10\nNaN,10,10,10\n-3,10,10,10\n\
+                       2,inf,10,10\n";
+        let mut salida = Vec::new();
+        let fila = |linea, motivo: &str| FilaInvalida { linea, motivo: motivo.to_string() };
+
+        assert_eq!(
+            procesar_csv_a_csv(&servicios_csv(), entrada.as_bytes(), &mut salida),
+            Err(OptimizadorError::FilasInvalidas(vec![
+                fila(2, "'diez' no es un número"),
+                fila(4, "se esperaban 4 columnas, hay 3"),
+                fila(5, "peso debe ser positivo, se recibió NaN"),
+                fila(6, "peso debe ser positivo, se recibió -3"),
+                fila(7, "ancho debe ser positivo, se recibió inf"),
+            ]))
+        );
+        // La fila válida se cotiza igual.
+        assert_eq!(
+            String::from_utf8(salida).unwrap(),
+            "peso,ancho,alto,profundidad,servicio,costo\n1,10,10,10,Moto,5.00\n"
+        );
+    }
+
+    #[test]
+    fn test_leer_catalogo_version_actual() {
+        let catalogo = Catalogo::new(vec![ServicioDeMensajeria { nombre: "Local".to_string(), ..Default::default() }]);
+        let json = catalogo_a_json(&catalogo).unwrap();
+
+        let leido = leer_catalogo(json.as_bytes()).unwrap();
+        assert_eq!(leido.schema_version, SCHEMA_VERSION);
+        assert_eq!(leido.servicios[0].nombre, "Local");
+    }
+
+    #[test]
+    fn test_leer_catalogo_version_futura() {
+        // Una versión futura podría cambiar la forma de los servicios.
+        let json = format!(r#"{{"schema_version": {}, "servicios": {{"nuevo": true}}}}"#, SCHEMA_VERSION + 1);
+        assert!(matches!(
+            leer_catalogo(json.as_bytes()),
+            Err(CargaError::VersionNoSoportada { encontrada, soportada })
+                if encontrada == SCHEMA_VERSION + 1 && soportada == SCHEMA_VERSION
+        ));
+    }
+
+    #[test]
+    fn test_servicios_conservan_costos_tras_ida_y_vuelta_json() {
+        let servicios = vec![
+            ServicioDeMensajeria {
+                nombre: "Simple".to_string(),
+                tarifa: Tarifa { costo_base: 5.0, costo_por_kg: 1.5, costo_por_volumen_cm3: 0.001, ..Default::default() },
+                ..Default::default()
+            },
+            ServicioDeMensajeria {
+                nombre: "Con recargos".to_string(),
+                tarifa: Tarifa {
+                    costo_base: 8.0,
+                    costo_por_kg: 1.2,
+                    costo_por_volumen_cm3: 0.0008,
+                    recargos: vec![Recargo::Combustible { porcentaje: 7.5 }, Recargo::Fragil { monto: 3.0 }],
+                    ..Default::default()
+                },
+                moneda: Some(Moneda::Pen),
+                ..Default::default()
+            },
+            ServicioDeMensajeria {
+                nombre: "Con límites".to_string(),
+                tarifa: Tarifa {
+                    costo_base: 20.0,
+                    costo_por_kg: 1.0 / 3.0,
+                    costo_por_volumen_cm3: 0.002,
+                    tramos_peso: vec![TramoPeso { hasta_kg: 5.0, costo_por_kg: 0.9 }],
+                    ..Default::default()
+                },
+                peso_maximo_kg: Some(30.0),
+                lado_maximo_cm: Some(120.0),
+                ..Default::default()
+            },
+        ];
+        let paquetes: Vec<Paquete> = [(0.5, 10.0), (5.5, 20.0), (17.3, 45.0)]
+            .into_iter()
+            .map(|(peso_kg, lado)| Paquete::new(peso_kg, Dimensiones::new(lado, lado * 0.7, lado * 1.3)))
+            .collect();
+
+        for servicio in &servicios {
+            let json = servicio_a_json(servicio).unwrap();
+            let restaurado = servicio_desde_json(&json).unwrap();
+            assert_eq!(restaurado.nombre, servicio.nombre);
+            for paquete in &paquetes {
+                assert_eq!(restaurado.calcular_costo(paquete), servicio.calcular_costo(paquete));
+                assert_eq!(restaurado.admite(paquete), servicio.admite(paquete));
+            }
+        }
+    }
+
+    #[test]
+    fn test_servicio_con_metadatos_ida_y_vuelta_json() {
+        let metadatos = MetadatosServicio {
+            color_hex: "#FFCC00".to_string(),
+            logo_url: Some("https://example.com/logo.png".to_string()),
+        };
+        let servicio = ServicioDeMensajeria {
+            nombre: "DHL Express".to_string(),
+            metadatos: Some(metadatos.clone()),
+            ..Default::default()
+        };
+
+        let json = servicio_a_json(&servicio).unwrap();
+        assert!(json.contains(r##""metadatos":{"color_hex":"#FFCC00","logo_url":"https://example.com/logo.png"}"##));
+        assert_eq!(servicio_desde_json(&json).unwrap().metadatos, Some(metadatos));
+
+        // Sin metadatos no se serializa el campo y sigue siendo compatible.
+        let sin_metadatos = ServicioDeMensajeria { nombre: "Local".to_string(), ..Default::default() };
+        let json = servicio_a_json(&sin_metadatos).unwrap();
+        assert!(!json.contains("metadatos"));
+        assert_eq!(servicio_desde_json(&json).unwrap().metadatos, None);
+    }
+}
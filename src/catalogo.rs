@@ -0,0 +1,435 @@
+use serde_json::Value;
+
+use crate::{Dimensiones, Recargo, ServicioDeMensajeria};
+
+/// Cuántas veces la mediana del catálogo debe superar un costo base para
+/// considerarlo atípico.
+const FACTOR_BASE_ATIPICA: f64 = 3.0;
+
+/// Revisa un catálogo antes de publicarlo y devuelve advertencias sobre
+/// tarifas sospechosas.
+///
+/// Señala tarifas negativas, servicios que no cobran ni por peso ni por
+/// volumen, costos base más de tres veces por encima de la mediana del
+/// catálogo y combinaciones imposibles, como límites o divisores no positivos
+/// o tramos de peso desordenados.
+pub fn detectar_anomalias(servicios: &[ServicioDeMensajeria]) -> Vec<String> {
+    let mediana_base = mediana(servicios.iter().map(|servicio| servicio.tarifa.costo_base).collect());
+    let mut anomalias = Vec::new();
+
+    for servicio in servicios {
+        let nombre = &servicio.nombre;
+        let tarifa = &servicio.tarifa;
+        anomalias.extend(errores_de_servicio(servicio));
+        if tarifa.costo_por_kg == 0.0 && tarifa.costo_por_volumen_cm3 == 0.0 && tarifa.tramos_peso.is_empty() {
+            anomalias.push(format!("{}: no cobra por peso ni por volumen", nombre));
+        }
+        if let Some(mediana) = mediana_base
+            && mediana > 0.0
+            && tarifa.costo_base > mediana * FACTOR_BASE_ATIPICA
+        {
+            anomalias.push(format!(
+                "{}: costo_base {:.2} supera {} veces la mediana del catálogo ({:.2})",
+                nombre, tarifa.costo_base, FACTOR_BASE_ATIPICA, mediana
+            ));
+        }
+    }
+    anomalias
+}
+
+/// Valida un catálogo antes de usarlo y reúne todos los errores encontrados
+/// en lugar de detenerse en el primero.
+///
+/// A diferencia de `detectar_anomalias`, sólo reporta problemas que hacen el
+/// catálogo inservible: nombres vacíos o repetidos, montos negativos y
+/// límites, divisores o tramos incoherentes.
+pub fn validar_catalogo(servicios: &[ServicioDeMensajeria]) -> Result<(), Vec<String>> {
+    let mut errores = Vec::new();
+
+    for (indice, servicio) in servicios.iter().enumerate() {
+        if servicio.nombre.trim().is_empty() {
+            errores.push(format!("el servicio en la posición {} no tiene nombre", indice));
+        }
+        // Se reporta una sola vez por nombre, en su primera repetición.
+        let anteriores = servicios[..indice].iter().filter(|anterior| anterior.nombre == servicio.nombre);
+        if anteriores.count() == 1 {
+            errores.push(format!("{}: el nombre está repetido", servicio.nombre));
+        }
+        errores.extend(errores_de_servicio(servicio));
+    }
+
+    if errores.is_empty() { Ok(()) } else { Err(errores) }
+}
+
+/// Problemas de un servicio que ninguna tarifa válida puede tener.
+fn errores_de_servicio(servicio: &ServicioDeMensajeria) -> Vec<String> {
+    let nombre = &servicio.nombre;
+    let tarifa = &servicio.tarifa;
+    let mut errores = Vec::new();
+
+    for (campo, valor) in [
+        ("costo_base", tarifa.costo_base),
+        ("costo_por_kg", tarifa.costo_por_kg),
+        ("costo_por_volumen_cm3", tarifa.costo_por_volumen_cm3),
+        ("costo_por_km", tarifa.costo_por_km),
+        ("recargo_peligroso", servicio.recargo_peligroso),
+    ] {
+        if valor < 0.0 {
+            errores.push(format!("{}: {} es negativo ({})", nombre, campo, valor));
+        }
+    }
+    for (campo, limite) in [
+        ("peso_maximo_kg", servicio.peso_maximo_kg),
+        ("lado_maximo_cm", servicio.lado_maximo_cm),
+        ("divisor_volumetrico", tarifa.divisor_volumetrico),
+    ] {
+        if let Some(limite) = limite
+            && limite <= 0.0
+        {
+            errores.push(format!("{}: {} debe ser positivo ({})", nombre, campo, limite));
+        }
+    }
+    if let Some(piso) = servicio.piso_global
+        && !(piso >= 0.0 && piso.is_finite())
+    {
+        errores.push(format!("{}: piso_global debe ser finito y no negativo ({})", nombre, piso));
+    }
+    let mut hasta_anterior = 0.0;
+    for tramo in &tarifa.tramos_peso {
+        if tramo.hasta_kg <= hasta_anterior {
+            errores.push(format!("{}: los tramos de peso no están en orden creciente", nombre));
+            break;
+        }
+        hasta_anterior = tramo.hasta_kg;
+    }
+    if tarifa.tramos_peso.iter().any(|tramo| tramo.costo_por_kg < 0.0) {
+        errores.push(format!("{}: hay tramos de peso con costo negativo", nombre));
+    }
+    for recargo in &tarifa.recargos {
+        let imposible = match *recargo {
+            Recargo::Combustible { porcentaje } | Recargo::TemporadaAlta { porcentaje } => porcentaje <= -100.0,
+            Recargo::Fragil { monto } | Recargo::Sobredimension { monto } => monto < 0.0,
+        };
+        if imposible {
+            errores.push(format!("{}: el recargo {:?} reduce el costo", nombre, recargo));
+        }
+    }
+    errores
+}
+
+/// Indica si dos servicios cobran lo mismo, sin importar su nombre, para
+/// depurar catálogos con entradas duplicadas.
+///
+/// Compara cada campo de la tarifa, los límites, el piso global, los
+/// descuentos por volumen y las cajas estándar (sin importar su orden), la
+/// moneda, el redondeo y el trato de la mercancía peligrosa; los valores numéricos pueden diferir en
+/// hasta `tolerancia`.
+pub fn son_equivalentes(a: &ServicioDeMensajeria, b: &ServicioDeMensajeria, tolerancia: f64) -> bool {
+    let cerca = |x: f64, y: f64| (x - y).abs() <= tolerancia;
+    let opcionales_cerca = |x: Option<f64>, y: Option<f64>| match (x, y) {
+        (Some(x), Some(y)) => cerca(x, y),
+        (x, y) => x.is_none() && y.is_none(),
+    };
+    let recargos_cerca = |x: &Recargo, y: &Recargo| match (*x, *y) {
+        (Recargo::Combustible { porcentaje: x }, Recargo::Combustible { porcentaje: y })
+        | (Recargo::TemporadaAlta { porcentaje: x }, Recargo::TemporadaAlta { porcentaje: y })
+        | (Recargo::Fragil { monto: x }, Recargo::Fragil { monto: y })
+        | (Recargo::Sobredimension { monto: x }, Recargo::Sobredimension { monto: y }) => cerca(x, y),
+        _ => false,
+    };
+
+    let descuentos_ordenados = |servicio: &ServicioDeMensajeria| {
+        let mut descuentos = servicio.descuentos_por_volumen.clone();
+        descuentos.sort_by_key(|&(minimo, _)| minimo);
+        descuentos
+    };
+    let (da, db) = (descuentos_ordenados(a), descuentos_ordenados(b));
+    let cajas_ordenadas = |servicio: &ServicioDeMensajeria| {
+        let mut cajas: Vec<Dimensiones> = servicio.cajas_estandar.iter().map(Dimensiones::en_cm).collect();
+        cajas.sort_by(|x, y| x.volumen_cm3().total_cmp(&y.volumen_cm3()));
+        cajas
+    };
+    let (ca, cb) = (cajas_ordenadas(a), cajas_ordenadas(b));
+
+    let (ta, tb) = (&a.tarifa, &b.tarifa);
+    cerca(ta.costo_base, tb.costo_base)
+        && cerca(ta.costo_por_kg, tb.costo_por_kg)
+        && cerca(ta.costo_por_volumen_cm3, tb.costo_por_volumen_cm3)
+        && cerca(ta.costo_por_km, tb.costo_por_km)
+        && opcionales_cerca(ta.divisor_volumetrico, tb.divisor_volumetrico)
+        && opcionales_cerca(ta.incremento_peso_kg, tb.incremento_peso_kg)
+        && opcionales_cerca(ta.ratio_maximo_volumetrico, tb.ratio_maximo_volumetrico)
+        && opcionales_cerca(ta.tasa_seguro_pct, tb.tasa_seguro_pct)
+        && opcionales_cerca(ta.volumen_incluido_cm3, tb.volumen_incluido_cm3)
+        && opcionales_cerca(ta.costo_por_kg_maximo, tb.costo_por_kg_maximo)
+        && ta.recargos.len() == tb.recargos.len()
+        && ta.recargos.iter().zip(&tb.recargos).all(|(x, y)| recargos_cerca(x, y))
+        && ta.tramos_peso.len() == tb.tramos_peso.len()
+        && ta
+            .tramos_peso
+            .iter()
+            .zip(&tb.tramos_peso)
+            .all(|(x, y)| cerca(x.hasta_kg, y.hasta_kg) && cerca(x.costo_por_kg, y.costo_por_kg))
+        && opcionales_cerca(a.peso_maximo_kg, b.peso_maximo_kg)
+        && opcionales_cerca(a.lado_maximo_cm, b.lado_maximo_cm)
+        && opcionales_cerca(a.piso_global, b.piso_global)
+        && da.len() == db.len()
+        && da.iter().zip(&db).all(|(&(min_a, pct_a), &(min_b, pct_b))| min_a == min_b && cerca(pct_a, pct_b))
+        && ca.len() == cb.len()
+        && ca
+            .iter()
+            .zip(&cb)
+            .all(|(x, y)| cerca(x.ancho, y.ancho) && cerca(x.alto, y.alto) && cerca(x.profundidad, y.profundidad))
+        && a.moneda == b.moneda
+        && a.redondeo == b.redondeo
+        && a.acepta_peligrosos == b.acepta_peligrosos
+        && cerca(a.recargo_peligroso, b.recargo_peligroso)
+}
+
+/// Cambio de un servicio entre dos versiones de un catálogo.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CambioTarifa {
+    Agregado { servicio: String },
+    Eliminado { servicio: String },
+    /// El servicio existe en ambas versiones con algún campo distinto.
+    Modificado { servicio: String, campos: Vec<CambioCampo> },
+}
+
+/// Campo que cambió, con su ruta (p. ej. `tarifa.costo_base`) y sus valores
+/// en JSON antes y después. Un campo ausente se muestra como `null`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CambioCampo {
+    pub campo: String,
+    pub antes: String,
+    pub despues: String,
+}
+
+/// Compara dos versiones de un catálogo para auditar cambios de precios.
+///
+/// Los servicios se emparejan por nombre y se comparan campo a campo sobre
+/// su representación JSON, así que cualquier campo nuevo del servicio entra en
+/// la comparación. Primero se listan los servicios eliminados o modificados,
+/// en el orden de `antiguo`, y luego los agregados, en el orden de `nuevo`.
+pub fn diff_catalogos(antiguo: &[ServicioDeMensajeria], nuevo: &[ServicioDeMensajeria]) -> Vec<CambioTarifa> {
+    let mut cambios = Vec::new();
+
+    for anterior in antiguo {
+        let Some(actual) = nuevo.iter().find(|servicio| servicio.nombre == anterior.nombre) else {
+            cambios.push(CambioTarifa::Eliminado { servicio: anterior.nombre.clone() });
+            continue;
+        };
+        let mut campos_antes = Vec::new();
+        let mut campos_despues = Vec::new();
+        aplanar("", &serde_json::to_value(anterior).unwrap_or_default(), &mut campos_antes);
+        aplanar("", &serde_json::to_value(actual).unwrap_or_default(), &mut campos_despues);
+
+        let mut rutas: Vec<&String> = campos_antes.iter().chain(&campos_despues).map(|(ruta, _)| ruta).collect();
+        rutas.sort();
+        rutas.dedup();
+        let valor = |campos: &[(String, Value)], ruta: &String| {
+            campos.iter().find(|(r, _)| r == ruta).map(|(_, v)| v.clone()).unwrap_or(Value::Null)
+        };
+        let campos: Vec<CambioCampo> = rutas
+            .into_iter()
+            .filter_map(|ruta| {
+                let (antes, despues) = (valor(&campos_antes, ruta), valor(&campos_despues, ruta));
+                (antes != despues).then(|| CambioCampo {
+                    campo: ruta.clone(),
+                    antes: antes.to_string(),
+                    despues: despues.to_string(),
+                })
+            })
+            .collect();
+        if !campos.is_empty() {
+            cambios.push(CambioTarifa::Modificado { servicio: anterior.nombre.clone(), campos });
+        }
+    }
+    for actual in nuevo {
+        if !antiguo.iter().any(|servicio| servicio.nombre == actual.nombre) {
+            cambios.push(CambioTarifa::Agregado { servicio: actual.nombre.clone() });
+        }
+    }
+    cambios
+}
+
+/// Recorre los objetos JSON anidados y acumula cada hoja con su ruta.
+fn aplanar(prefijo: &str, valor: &Value, campos: &mut Vec<(String, Value)>) {
+    match valor {
+        Value::Object(mapa) => {
+            for (clave, hijo) in mapa {
+                let ruta = if prefijo.is_empty() { clave.clone() } else { format!("{}.{}", prefijo, clave) };
+                aplanar(&ruta, hijo, campos);
+            }
+        }
+        hoja => campos.push((prefijo.to_string(), hoja.clone())),
+    }
+}
+
+/// Mediana de una lista de valores, o `None` si está vacía.
+pub(crate) fn mediana(mut valores: Vec<f64>) -> Option<f64> {
+    if valores.is_empty() {
+        return None;
+    }
+    valores.sort_by(f64::total_cmp);
+    let medio = valores.len() / 2;
+    if valores.len().is_multiple_of(2) {
+        Some((valores[medio - 1] + valores[medio]) / 2.0)
+    } else {
+        Some(valores[medio])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Tarifa, UnidadLongitud};
+
+    fn servicio(nombre: &str, costo_base: f64, costo_por_kg: f64) -> ServicioDeMensajeria {
+        ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base, costo_por_kg, costo_por_volumen_cm3: 0.001, ..Default::default() },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_detecta_servicio_atipico() {
+        let servicios = vec![
+            servicio("Rappi Courier", 5.0, 1.5),
+            servicio("Uber Paquetes", 8.0, 1.2),
+            servicio("Olva", 6.0, 1.1),
+            servicio("Caro Express", 40.0, 1.0),
+        ];
+        assert_eq!(
+            detectar_anomalias(&servicios),
+            vec!["Caro Express: costo_base 40.00 supera 3 veces la mediana del catálogo (7.00)".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_detecta_tarifas_negativas_y_combinaciones_imposibles() {
+        let mut roto = servicio("Roto", 5.0, -1.0);
+        roto.peso_maximo_kg = Some(0.0);
+        let mut gratis = servicio("Gratis", 5.0, 0.0);
+        gratis.tarifa.costo_por_volumen_cm3 = 0.0;
+
+        let anomalias = detectar_anomalias(&[servicio("Normal", 5.0, 1.0), roto, gratis]);
+        assert_eq!(
+            anomalias,
+            vec![
+                "Roto: costo_por_kg es negativo (-1)".to_string(),
+                "Roto: peso_maximo_kg debe ser positivo (0)".to_string(),
+                "Gratis: no cobra por peso ni por volumen".to_string(),
+            ]
+        );
+        assert!(detectar_anomalias(&[servicio("Normal", 5.0, 1.0)]).is_empty());
+    }
+
+    #[test]
+    fn test_servicios_con_otro_nombre_son_equivalentes() {
+        let original = servicio("Olva Courier", 6.0, 1.1);
+        let reetiquetado = servicio("Olva Express", 6.0, 1.1 + 1e-12);
+        assert!(son_equivalentes(&original, &reetiquetado, 1e-9));
+
+        let mut con_recargo = servicio("Olva Express", 6.0, 1.1);
+        con_recargo.tarifa.recargos.push(Recargo::Fragil { monto: 2.0 });
+        assert!(!son_equivalentes(&original, &con_recargo, 1e-9));
+    }
+
+    #[test]
+    fn test_piso_global_distinto_no_es_equivalente() {
+        let original = servicio("Olva Courier", 6.0, 1.1);
+        let con_piso = ServicioDeMensajeria { piso_global: Some(20.0), ..servicio("Olva Express", 6.0, 1.1) };
+        assert!(!son_equivalentes(&original, &con_piso, 1e-9));
+        let mismo_piso = ServicioDeMensajeria { piso_global: Some(20.0), ..servicio("Olva Plus", 6.0, 1.1) };
+        assert!(son_equivalentes(&con_piso, &mismo_piso, 1e-9));
+    }
+
+    #[test]
+    fn test_descuentos_por_volumen_se_comparan_sin_orden() {
+        let con_descuentos = |descuentos_por_volumen| ServicioDeMensajeria {
+            descuentos_por_volumen,
+            ..servicio("Olva", 6.0, 1.1)
+        };
+        let original = con_descuentos(vec![(100, 5.0), (500, 15.0)]);
+        assert!(son_equivalentes(&original, &con_descuentos(vec![(500, 15.0), (100, 5.0 + 1e-12)]), 1e-9));
+        assert!(!son_equivalentes(&original, &con_descuentos(vec![(100, 5.0), (500, 20.0)]), 1e-9));
+        assert!(!son_equivalentes(&original, &con_descuentos(vec![(100, 5.0)]), 1e-9));
+        assert!(!son_equivalentes(&original, &servicio("Olva", 6.0, 1.1), 1e-9));
+    }
+
+    #[test]
+    fn test_cajas_estandar_distintas_no_son_equivalentes() {
+        let con_cajas = |cajas_estandar| ServicioDeMensajeria { cajas_estandar, ..servicio("Olva", 6.0, 1.1) };
+        let original = con_cajas(vec![Dimensiones::new(40.0, 30.0, 30.0), Dimensiones::new(20.0, 15.0, 10.0)]);
+        let en_mm = Dimensiones::con_unidad(200.0, 150.0, 100.0, UnidadLongitud::Mm);
+        assert!(son_equivalentes(&original, &con_cajas(vec![en_mm, Dimensiones::new(40.0, 30.0, 30.0)]), 1e-9));
+        assert!(!son_equivalentes(&original, &con_cajas(vec![Dimensiones::new(40.0, 30.0, 30.0)]), 1e-9));
+        assert!(!son_equivalentes(&original, &servicio("Olva", 6.0, 1.1), 1e-9));
+    }
+
+    #[test]
+    fn test_validar_catalogo_rechaza_piso_global_invalido() {
+        for piso in [-1.0, f64::NAN, f64::INFINITY] {
+            let roto = ServicioDeMensajeria { piso_global: Some(piso), ..servicio("Olva", 6.0, 1.1) };
+            assert_eq!(
+                validar_catalogo(&[roto]),
+                Err(vec![format!("Olva: piso_global debe ser finito y no negativo ({})", piso)])
+            );
+        }
+    }
+
+    #[test]
+    fn test_diff_catalogos() {
+        let antiguo = vec![servicio("Rappi Courier", 5.0, 1.5), servicio("Olva", 6.0, 1.1), servicio("Local", 3.0, 1.0)];
+        let mut olva_nuevo = servicio("Olva", 6.5, 1.1);
+        olva_nuevo.peso_maximo_kg = Some(30.0);
+        let nuevo = vec![servicio("Rappi Courier", 5.0, 1.5), olva_nuevo, servicio("Uber Paquetes", 8.0, 1.2)];
+
+        assert_eq!(
+            diff_catalogos(&antiguo, &nuevo),
+            vec![
+                CambioTarifa::Modificado {
+                    servicio: "Olva".to_string(),
+                    campos: vec![
+                        CambioCampo {
+                            campo: "peso_maximo_kg".to_string(),
+                            antes: "null".to_string(),
+                            despues: "30.0".to_string(),
+                        },
+                        CambioCampo {
+                            campo: "tarifa.costo_base".to_string(),
+                            antes: "6.0".to_string(),
+                            despues: "6.5".to_string(),
+                        },
+                    ],
+                },
+                CambioTarifa::Eliminado { servicio: "Local".to_string() },
+                CambioTarifa::Agregado { servicio: "Uber Paquetes".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validar_catalogo_reporta_todos_los_problemas() {
+        let mut negativo = servicio("Olva", 5.0, -1.0);
+        negativo.lado_maximo_cm = Some(-10.0);
+        let servicios = vec![
+            servicio("Olva", 6.0, 1.1),
+            negativo,
+            servicio("", 4.0, 1.0),
+            servicio("Olva", 7.0, 1.0),
+        ];
+
+        assert_eq!(
+            validar_catalogo(&servicios),
+            Err(vec![
+                "Olva: el nombre está repetido".to_string(),
+                "Olva: costo_por_kg es negativo (-1)".to_string(),
+                "Olva: lado_maximo_cm debe ser positivo (-10)".to_string(),
+                "el servicio en la posición 2 no tiene nombre".to_string(),
+            ])
+        );
+        assert_eq!(validar_catalogo(&[servicio("Rappi Courier", 5.0, 1.5)]), Ok(()));
+    }
+}
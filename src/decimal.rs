@@ -0,0 +1,175 @@
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::{Paquete, Recargo, RedondeoModo, ServicioDeMensajeria, Tarifa};
+
+/// Convierte un valor de la tarifa o del paquete a decimal. Devuelve `None`
+/// para los valores que un `Decimal` no puede representar (NaN, infinitos o
+/// fuera de su rango).
+fn decimal(valor: f64) -> Option<Decimal> {
+    Decimal::from_f64(valor)
+}
+
+impl Tarifa {
+    /// Igual que `costo_por_peso`, pero en decimal.
+    fn costo_por_peso_decimal(&self, peso_kg: Decimal) -> Option<Decimal> {
+        let costo = self.costo_por_peso_en_tramos_decimal(peso_kg)?;
+        match self.costo_por_kg_maximo {
+            Some(tope) => Some(costo.min(decimal(tope)?)),
+            None => Some(costo),
+        }
+    }
+
+    /// Igual que `costo_por_peso_en_tramos`, pero en decimal.
+    fn costo_por_peso_en_tramos_decimal(&self, peso_kg: Decimal) -> Option<Decimal> {
+        let mut costo = Decimal::ZERO;
+        let mut desde_kg = Decimal::ZERO;
+        for tramo in &self.tramos_peso {
+            if peso_kg <= desde_kg {
+                return Some(costo);
+            }
+            let hasta_kg = decimal(tramo.hasta_kg)?;
+            let en_tramo = peso_kg.min(hasta_kg).checked_sub(desde_kg)?.checked_mul(decimal(tramo.costo_por_kg)?)?;
+            costo = costo.checked_add(en_tramo)?;
+            desde_kg = hasta_kg;
+        }
+        let excedente = peso_kg.checked_sub(desde_kg)?.max(Decimal::ZERO).checked_mul(decimal(self.costo_por_kg)?)?;
+        costo.checked_add(excedente)
+    }
+}
+
+impl Recargo {
+    /// Igual que `aplicar`, pero en decimal.
+    fn aplicar_decimal(&self, acumulado: Decimal) -> Option<Decimal> {
+        match *self {
+            Recargo::Combustible { porcentaje } | Recargo::TemporadaAlta { porcentaje } => {
+                let factor = Decimal::ONE.checked_add(decimal(porcentaje)?.checked_div(Decimal::ONE_HUNDRED)?)?;
+                acumulado.checked_mul(factor)
+            }
+            Recargo::Fragil { monto } | Recargo::Sobredimension { monto } => acumulado.checked_add(decimal(monto)?),
+        }
+    }
+}
+
+impl RedondeoModo {
+    /// Igual que `aplicar`, pero en decimal; aquí no hace falta tolerancia.
+    fn aplicar_decimal(&self, monto: Decimal) -> Decimal {
+        match self {
+            RedondeoModo::Centavos => monto.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero),
+            RedondeoModo::CentavosSuperior => monto.round_dp_with_strategy(2, RoundingStrategy::ToPositiveInfinity),
+            RedondeoModo::EnteroSuperior => monto.ceil(),
+        }
+    }
+}
+
+impl ServicioDeMensajeria {
+    /// Calcula el costo total como `calcular_costo`, pero con aritmética
+    /// decimal de punto fijo para que los montos no arrastren errores de
+    /// representación binaria.
+    ///
+    /// Devuelve `None` si algún valor de la tarifa o del paquete no es
+    /// representable en decimal (NaN, infinitos) o si el cálculo se desborda.
+    pub fn calcular_costo_decimal(&self, paquete: &Paquete) -> Option<Decimal> {
+        let factor = decimal(paquete.dimensiones.unidad.en_cm())?;
+        let volumen = [paquete.dimensiones.alto, paquete.dimensiones.profundidad]
+            .into_iter()
+            .try_fold(decimal(paquete.dimensiones.ancho)?, |volumen, lado| volumen.checked_mul(decimal(lado)?))?
+            .checked_mul(factor)?
+            .checked_mul(factor)?
+            .checked_mul(factor)?;
+
+        let peso_real = decimal(paquete.peso_kg)?;
+        let peso_facturable = match self.tarifa.divisor_volumetrico.map(decimal) {
+            Some(Some(divisor)) if divisor > Decimal::ZERO => {
+                let mut volumetrico = volumen.checked_div(divisor)?;
+                if let Some(ratio) = self.tarifa.ratio_maximo_volumetrico {
+                    volumetrico = volumetrico.min(peso_real.checked_mul(decimal(ratio)?)?);
+                }
+                peso_real.max(volumetrico)
+            }
+            Some(None) => return None,
+            _ => peso_real,
+        };
+
+        let volumen_cobrado =
+            volumen.checked_sub(decimal(self.tarifa.volumen_incluido_cm3.unwrap_or(0.0))?)?.max(Decimal::ZERO);
+        let subtotal = decimal(self.tarifa.costo_base)?
+            .checked_add(self.tarifa.costo_por_peso_decimal(peso_facturable)?)?
+            .checked_add(decimal(self.tarifa.costo_por_volumen_cm3)?.checked_mul(volumen_cobrado)?)?
+            .checked_add(decimal(self.cargo_peligroso(paquete))?)?;
+        let costo = self
+            .tarifa
+            .recargos
+            .iter()
+            .try_fold(subtotal, |acumulado, recargo| recargo.aplicar_decimal(acumulado))?;
+        let costo = match self.redondeo {
+            Some(modo) => modo.aplicar_decimal(costo),
+            None => costo,
+        };
+        match self.piso_global {
+            Some(piso) => Some(costo.max(decimal(piso)?)),
+            None => Some(costo),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::prelude::ToPrimitive;
+
+    use super::*;
+    use crate::Dimensiones;
+
+    #[test]
+    fn test_decimal_coincide_con_f64() {
+        let servicio = ServicioDeMensajeria {
+            nombre: "Rappi Courier".to_string(),
+            tarifa: Tarifa {
+                costo_base: 5.0,
+                costo_por_kg: 1.5,
+                costo_por_volumen_cm3: 0.001,
+                recargos: vec![Recargo::Combustible { porcentaje: 8.0 }, Recargo::Fragil { monto: 2.5 }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let paquete = Paquete::new(5.5, Dimensiones::new(15.0, 10.0, 20.0));
+
+        let en_decimal = servicio.calcular_costo_decimal(&paquete).unwrap().to_f64().unwrap();
+        assert!((en_decimal - servicio.calcular_costo(&paquete)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_decimal_evita_error_de_representacion() {
+        // En f64, 0.1 + 0.2 da 0.30000000000000004.
+        let servicio = ServicioDeMensajeria {
+            nombre: "Centavos Exactos".to_string(),
+            tarifa: Tarifa { costo_base: 0.1, costo_por_kg: 0.2, ..Default::default() },
+            ..Default::default()
+        };
+        let paquete = Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0));
+
+        assert_eq!(servicio.calcular_costo_decimal(&paquete), Some(Decimal::new(3, 1)));
+        // El acumulador de punto fijo de `calcular_costo` también lo evita.
+        assert_eq!(servicio.calcular_costo(&paquete), 0.3);
+    }
+
+    #[test]
+    fn test_decimal_no_representable_o_desbordado_es_none() {
+        let servicio = ServicioDeMensajeria {
+            nombre: "Volumen".to_string(),
+            tarifa: Tarifa { costo_base: 1.0, costo_por_volumen_cm3: 0.001, ..Default::default() },
+            ..Default::default()
+        };
+        let enorme = Paquete::new(1.0, Dimensiones::new(1e10, 1e10, 1e10));
+        assert!(servicio.calcular_costo(&enorme).is_finite());
+        assert_eq!(servicio.calcular_costo_decimal(&enorme), None);
+
+        let tarifa_rota = ServicioDeMensajeria {
+            tarifa: Tarifa { costo_base: f64::NAN, ..Default::default() },
+            ..servicio
+        };
+        let paquete = Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0));
+        assert_eq!(tarifa_rota.calcular_costo_decimal(&paquete), None);
+    }
+}
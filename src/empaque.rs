@@ -0,0 +1,404 @@
+use crate::{Dimensiones, OptimizadorError, Paquete, ServicioDeMensajeria, mejor_opcion};
+
+/// Envío armado a partir de una lista de empaque: el peso y el volumen
+/// totales de sus artículos.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Envio {
+    pub peso_kg: f64,
+    pub volumen_cm3: f64,
+}
+
+impl Envio {
+    /// Suma los pesos y los volúmenes de artículos dados como pares
+    /// `(peso_kg, dimensiones)`.
+    pub fn desde_items(items: &[(f64, Dimensiones)]) -> Envio {
+        Envio {
+            peso_kg: items.iter().map(|&(peso_kg, _)| peso_kg).sum(),
+            volumen_cm3: items.iter().map(|(_, dims)| dims.volumen_cm3()).sum(),
+        }
+    }
+
+    /// Paquete equivalente para cotizar: una caja cúbica con el volumen total.
+    pub fn como_paquete(&self) -> Paquete {
+        let lado = self.volumen_cm3.cbrt();
+        Paquete::new(self.peso_kg, Dimensiones::new(lado, lado, lado))
+    }
+}
+
+impl Paquete {
+    /// Fracción del volumen de `caja_ref` que ocupa el paquete.
+    ///
+    /// No se recorta a 1.0: un valor mayor indica que el paquete es más grande
+    /// que la caja de referencia. Si la caja no tiene volumen, devuelve
+    /// `f64::INFINITY`.
+    pub fn utilizacion_volumen(&self, caja_ref: &Dimensiones) -> f64 {
+        let volumen_ref = caja_ref.volumen_cm3();
+        if volumen_ref <= 0.0 {
+            return f64::INFINITY;
+        }
+        self.volumen_cm3() / volumen_ref
+    }
+}
+
+impl Dimensiones {
+    /// Indica si una caja con estas dimensiones cabe dentro de `caja`,
+    /// pudiendo rotarla.
+    pub fn cabe_en(&self, caja: &Dimensiones) -> bool {
+        let ordenar = |dims: Dimensiones| {
+            let mut lados = [dims.ancho, dims.alto, dims.profundidad];
+            lados.sort_by(f64::total_cmp);
+            lados
+        };
+        let (propios, de_la_caja) = (ordenar(self.en_cm()), ordenar(caja.en_cm()));
+        propios.iter().zip(&de_la_caja).all(|(lado, lado_caja)| lado <= lado_caja)
+    }
+}
+
+impl ServicioDeMensajeria {
+    /// Calcula el costo cobrando la caja estándar más chica, por volumen, en
+    /// la que cabe el paquete, con el peso real del paquete.
+    ///
+    /// Sin cajas estándar se cobra el paquete tal cual. Devuelve un error si
+    /// el paquete no cabe en ninguna.
+    pub fn calcular_costo_con_caja_estandar(&self, paquete: &Paquete) -> Result<f64, OptimizadorError> {
+        if self.cajas_estandar.is_empty() {
+            return Ok(self.calcular_costo(paquete));
+        }
+        let caja = self
+            .cajas_estandar
+            .iter()
+            .filter(|caja| paquete.dimensiones.cabe_en(caja))
+            .min_by(|a, b| a.volumen_cm3().total_cmp(&b.volumen_cm3()))
+            .ok_or_else(|| OptimizadorError::SinCajaEstandar { servicio: self.nombre.clone() })?;
+        let en_caja = Paquete { dimensiones: *caja, ..paquete.clone() };
+        Ok(self.calcular_costo(&en_caja))
+    }
+
+    /// Calcula el costo de un envío armado desde una lista de empaque, como
+    /// una sola caja cúbica con su volumen total.
+    pub fn calcular_costo_envio(&self, envio: &Envio) -> f64 {
+        self.calcular_costo(&envio.como_paquete())
+    }
+}
+
+/// Indica si la caja `interior` cabe dentro de `exterior` con los lados
+/// paralelos, pudiendo rotarla.
+pub fn puede_contener(exterior: &Dimensiones, interior: &Dimensiones) -> bool {
+    interior.cabe_en(exterior)
+}
+
+/// Costo de enviar `interior` dentro de `exterior` como un solo paquete: la
+/// caja exterior con el peso de ambos. Devuelve `None` si no cabe.
+pub fn costo_anidado(servicio: &ServicioDeMensajeria, exterior: &Paquete, interior: &Paquete) -> Option<f64> {
+    if !puede_contener(&exterior.dimensiones, &interior.dimensiones) {
+        return None;
+    }
+    let anidado = Paquete { peso_kg: exterior.peso_kg + interior.peso_kg, ..exterior.clone() };
+    Some(servicio.calcular_costo(&anidado))
+}
+
+/// Máximo de piezas en que `dividir_en_envios` acepta dividir un paquete.
+pub const MAX_PIEZAS_POR_ENVIO: usize = 10_000;
+
+/// Divide un paquete en el menor número de envíos de igual peso que no
+/// superen `peso_max_por_envio`.
+///
+/// Se modela como cortar la caja en rebanadas iguales a lo largo de su lado
+/// más largo: cada pieza conserva los otros dos lados, y el peso y el volumen
+/// totales se mantienen. Un paquete que ya cumple el máximo se devuelve
+/// entero. Devuelve un error si el peso del paquete no es finito o si harían
+/// falta más de `MAX_PIEZAS_POR_ENVIO` piezas.
+pub fn dividir_en_envios(paquete: &Paquete, peso_max_por_envio: f64) -> Result<Vec<Paquete>, OptimizadorError> {
+    if !(peso_max_por_envio > 0.0 && peso_max_por_envio.is_finite()) {
+        return Err(OptimizadorError::ParametroInvalido(format!(
+            "peso_max_por_envio debe ser positivo, se recibió {}",
+            peso_max_por_envio
+        )));
+    }
+    if !paquete.peso_kg.is_finite() {
+        return Err(OptimizadorError::ParametroInvalido(format!(
+            "peso_kg debe ser finito, se recibió {}",
+            paquete.peso_kg
+        )));
+    }
+    let piezas = (paquete.peso_kg / peso_max_por_envio).ceil().max(1.0);
+    if piezas > MAX_PIEZAS_POR_ENVIO as f64 {
+        return Err(OptimizadorError::ParametroInvalido(format!(
+            "harían falta {} piezas, el máximo es {}",
+            piezas, MAX_PIEZAS_POR_ENVIO
+        )));
+    }
+    Ok(dividir_en_piezas(paquete, piezas as usize))
+}
+
+/// Busca en cuántas piezas iguales conviene dividir el paquete, entre 1 y
+/// `max_piezas`, para que el costo total con el servicio sea mínimo, y
+/// devuelve `(piezas, costo total)`.
+///
+/// Las piezas se cortan como en `dividir_en_envios`. Dividir conviene cuando
+/// cada pieza cae en un tramo de peso más barato o dentro de los límites del
+/// servicio, y deja de convenir cuando pesa más el costo base repetido. Se
+/// descartan las divisiones cuyas piezas el servicio no admite; ante el mismo
+/// costo gana la de menos piezas. Si ninguna división se puede enviar,
+/// devuelve `(1, f64::MAX)`, el mismo centinela que
+/// `encontrar_opcion_mas_barata`. Un `max_piezas` de 0 se trata como 1: el
+/// paquete entero siempre se evalúa.
+pub fn split_optimo(servicio: &ServicioDeMensajeria, paquete: &Paquete, max_piezas: u32) -> (u32, f64) {
+    let mut mejor: Option<(u32, f64)> = None;
+    for piezas in 1..=max_piezas.max(1) {
+        let pieza = pieza_de(paquete, piezas as f64);
+        if !servicio.admite(&pieza) {
+            continue;
+        }
+        let costo = servicio.calcular_costo(&pieza) * piezas as f64;
+        if mejor.is_none_or(|(_, actual)| costo < actual) {
+            mejor = Some((piezas, costo));
+        }
+    }
+    mejor.unwrap_or((1, f64::MAX))
+}
+
+/// Sugiere achicar la caja cuando el paquete se factura por peso volumétrico
+/// con `divisor`, indicando cuántos cm³ sobran para facturar por peso real.
+///
+/// Devuelve `None` si el peso real ya es igual o mayor que el volumétrico.
+/// Requiere `divisor > 0`.
+pub fn recomendacion_empaque(paquete: &Paquete, divisor: f64) -> Result<Option<String>, OptimizadorError> {
+    if !(divisor > 0.0 && divisor.is_finite()) {
+        return Err(OptimizadorError::ParametroInvalido(format!(
+            "divisor debe ser positivo, se recibió {}",
+            divisor
+        )));
+    }
+    if paquete.peso_volumetrico(divisor) <= paquete.peso_kg {
+        return Ok(None);
+    }
+    let exceso_cm3 = paquete.volumen_cm3() - paquete.peso_kg * divisor;
+    Ok(Some(format!(
+        "Reduce el volumen en {:.0} cm³ para facturar por peso real",
+        exceso_cm3
+    )))
+}
+
+/// Costo mínimo de enviar los paquetes, ya sea por separado o consolidados en
+/// una sola caja.
+///
+/// La caja consolidada es el `Envio` de todos los paquetes. Cada alternativa
+/// usa el servicio más barato que la admite; devuelve `None` si ninguna de
+/// las dos puede enviarse.
+pub fn costo_consolidado(servicios: &[ServicioDeMensajeria], paquetes: &[Paquete]) -> Option<f64> {
+    let por_separado: Option<f64> = paquetes
+        .iter()
+        .map(|paquete| mejor_opcion(servicios, paquete).map(|opcion| opcion.costo))
+        .sum();
+
+    let items: Vec<(f64, Dimensiones)> = paquetes.iter().map(|paquete| (paquete.peso_kg, paquete.dimensiones)).collect();
+    let consolidado = Envio::desde_items(&items).como_paquete();
+    let en_una_caja = mejor_opcion(servicios, &consolidado).map(|opcion| opcion.costo);
+
+    match (por_separado, en_una_caja) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Corta el paquete en `piezas` partes iguales a lo largo de su lado más largo.
+pub(crate) fn dividir_en_piezas(paquete: &Paquete, piezas: usize) -> Vec<Paquete> {
+    vec![pieza_de(paquete, piezas as f64); piezas]
+}
+
+/// Una de las `n` piezas iguales que deja `dividir_en_piezas`.
+fn pieza_de(paquete: &Paquete, n: f64) -> Paquete {
+    let mut pieza = paquete.clone();
+    pieza.peso_kg /= n;
+    let dims = &mut pieza.dimensiones;
+    let lado_mayor = dims.ancho.max(dims.alto).max(dims.profundidad);
+    if dims.ancho == lado_mayor {
+        dims.ancho /= n;
+    } else if dims.alto == lado_mayor {
+        dims.alto /= n;
+    } else {
+        dims.profundidad /= n;
+    }
+    pieza
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Tarifa, TramoPeso, UnidadLongitud};
+
+    #[test]
+    fn test_utilizacion_volumen_mitad_de_caja() {
+        let paquete = Paquete::new(1.0, Dimensiones::new(20.0, 10.0, 10.0));
+        let caja = Dimensiones::new(20.0, 20.0, 10.0);
+        assert_eq!(paquete.utilizacion_volumen(&caja), 0.5);
+    }
+
+    #[test]
+    fn test_utilizacion_volumen_paquete_mayor_que_caja() {
+        let paquete = Paquete::new(1.0, Dimensiones::new(20.0, 20.0, 20.0));
+        let caja = Dimensiones::new(20.0, 20.0, 10.0);
+        assert_eq!(paquete.utilizacion_volumen(&caja), 2.0);
+        assert_eq!(paquete.utilizacion_volumen(&Dimensiones::new(0.0, 1.0, 1.0)), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_dividir_30kg_en_piezas_de_10kg() {
+        let paquete = Paquete::new(30.0, Dimensiones::new(60.0, 40.0, 30.0));
+        let piezas = dividir_en_envios(&paquete, 10.0).unwrap();
+
+        assert_eq!(piezas.len(), 3);
+        for pieza in &piezas {
+            assert_eq!(pieza.peso_kg, 10.0);
+            assert_eq!(pieza.dimensiones, Dimensiones::new(20.0, 40.0, 30.0));
+        }
+        let volumen_total: f64 = piezas.iter().map(Paquete::volumen_cm3).sum();
+        assert_eq!(volumen_total, paquete.volumen_cm3());
+    }
+
+    #[test]
+    fn test_dividir_paquete_liviano_no_divide() {
+        let paquete = Paquete::new(4.0, Dimensiones::new(10.0, 10.0, 10.0));
+        assert_eq!(dividir_en_envios(&paquete, 10.0).unwrap(), vec![paquete.clone()]);
+        assert!(dividir_en_envios(&paquete, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_dividir_rechaza_demasiadas_piezas() {
+        let paquete = Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0));
+        assert!(matches!(dividir_en_envios(&paquete, 1e-300), Err(OptimizadorError::ParametroInvalido(_))));
+        assert_eq!(dividir_en_envios(&paquete, 1.0 / MAX_PIEZAS_POR_ENVIO as f64).unwrap().len(), MAX_PIEZAS_POR_ENVIO);
+
+        let infinito = Paquete::new(f64::INFINITY, Dimensiones::new(10.0, 10.0, 10.0));
+        assert!(matches!(dividir_en_envios(&infinito, 10.0), Err(OptimizadorError::ParametroInvalido(_))));
+    }
+
+    #[test]
+    fn test_split_optimo_en_dos_piezas() {
+        // Los primeros 5 kg de cada pieza cuestan 1 por kg y el resto 4.
+        let servicio = ServicioDeMensajeria {
+            nombre: "Tramos".to_string(),
+            tarifa: Tarifa {
+                costo_base: 8.0,
+                costo_por_kg: 4.0,
+                tramos_peso: vec![TramoPeso { hasta_kg: 5.0, costo_por_kg: 1.0 }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let paquete = Paquete::new(10.0, Dimensiones::new(60.0, 40.0, 30.0));
+
+        // Una pieza: 8 + 5 + 20 = 33; dos: 2 * 13 = 26; tres: 3 * (8 + 10 / 3) = 34.
+        assert_eq!(servicio.calcular_costo(&paquete), 33.0);
+        let (piezas, costo) = split_optimo(&servicio, &paquete, 3);
+        assert_eq!(piezas, 2);
+        assert!((costo - 26.0).abs() < 1e-9);
+        assert_eq!(split_optimo(&servicio, &paquete, 1), (1, 33.0));
+        assert_eq!(split_optimo(&servicio, &paquete, 0), (1, 33.0));
+        // Cada división arma una sola pieza, así que un máximo grande no agota la memoria.
+        assert_eq!(split_optimo(&servicio, &paquete, 100_000).0, 2);
+    }
+
+    #[test]
+    fn test_recomendacion_empaque_si_domina_el_volumen() {
+        // 40000 cm³ / 5000 = 8 kg volumétricos frente a 2 kg reales.
+        let paquete = Paquete::new(2.0, Dimensiones::new(40.0, 25.0, 40.0));
+        assert_eq!(
+            recomendacion_empaque(&paquete, 5000.0).unwrap().as_deref(),
+            Some("Reduce el volumen en 30000 cm³ para facturar por peso real")
+        );
+    }
+
+    #[test]
+    fn test_recomendacion_empaque_si_domina_el_peso() {
+        let paquete = Paquete::new(10.0, Dimensiones::new(20.0, 20.0, 20.0));
+        assert_eq!(recomendacion_empaque(&paquete, 5000.0).unwrap(), None);
+    }
+
+    fn servicio(costo_base: f64, costo_por_kg: f64, peso_maximo_kg: Option<f64>) -> ServicioDeMensajeria {
+        ServicioDeMensajeria {
+            nombre: "Consolidador".to_string(),
+            tarifa: Tarifa { costo_base, costo_por_kg, ..Default::default() },
+            peso_maximo_kg,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_costo_consolidado_gana_con_costo_base_alto() {
+        // Por separado: 3 * (10 + 1); consolidado: 10 + 3.
+        let paquetes = vec![Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0)); 3];
+        assert_eq!(costo_consolidado(&[servicio(10.0, 1.0, None)], &paquetes), Some(13.0));
+    }
+
+    #[test]
+    fn test_costo_consolidado_no_gana_si_la_caja_excede_el_limite() {
+        // La caja de 6 kg supera el máximo de 5 kg: sólo se puede por separado.
+        let paquetes = vec![Paquete::new(3.0, Dimensiones::new(10.0, 10.0, 10.0)); 2];
+        let servicios = [servicio(1.0, 2.0, Some(5.0))];
+        assert_eq!(costo_consolidado(&servicios, &paquetes), Some(14.0));
+    }
+
+    #[test]
+    fn test_costo_anidado_si_la_caja_cabe() {
+        let exterior = Paquete::new(2.0, Dimensiones::new(40.0, 30.0, 30.0));
+        // Rotada, la caja de 35 x 20 x 25 entra en la exterior.
+        let interior = Paquete::new(1.5, Dimensiones::new(20.0, 35.0, 25.0));
+
+        assert!(puede_contener(&exterior.dimensiones, &interior.dimensiones));
+        assert_eq!(costo_anidado(&servicio(10.0, 2.0, None), &exterior, &interior), Some(17.0));
+    }
+
+    #[test]
+    fn test_costo_anidado_si_la_caja_no_cabe() {
+        let exterior = Paquete::new(2.0, Dimensiones::new(40.0, 30.0, 30.0));
+        let interior = Paquete::new(1.5, Dimensiones::new(45.0, 10.0, 10.0));
+
+        assert!(!puede_contener(&exterior.dimensiones, &interior.dimensiones));
+        assert_eq!(costo_anidado(&servicio(10.0, 2.0, None), &exterior, &interior), None);
+    }
+
+    #[test]
+    fn test_costo_con_caja_estandar_sube_a_la_siguiente() {
+        let servicio = ServicioDeMensajeria {
+            nombre: "Cajas".to_string(),
+            tarifa: Tarifa { costo_por_volumen_cm3: 0.001, ..Default::default() },
+            cajas_estandar: vec![
+                Dimensiones::new(40.0, 30.0, 30.0),
+                Dimensiones::new(20.0, 15.0, 10.0),
+                Dimensiones::new(30.0, 20.0, 20.0),
+            ],
+            ..Default::default()
+        };
+        // No cabe en la chica (20 x 15 x 10); rotado, cabe en la mediana.
+        let paquete = Paquete::new(1.0, Dimensiones::new(12.0, 25.0, 18.0));
+
+        let costo = servicio.calcular_costo_con_caja_estandar(&paquete).unwrap();
+        assert!((costo - 30.0 * 20.0 * 20.0 * 0.001).abs() < 1e-9);
+
+        let enorme = Paquete::new(1.0, Dimensiones::new(50.0, 10.0, 10.0));
+        assert_eq!(
+            servicio.calcular_costo_con_caja_estandar(&enorme),
+            Err(OptimizadorError::SinCajaEstandar { servicio: "Cajas".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_envio_desde_tres_items() {
+        let items = [
+            (1.5, Dimensiones::new(10.0, 10.0, 10.0)),
+            (2.0, Dimensiones::new(20.0, 10.0, 5.0)),
+            (0.5, Dimensiones::con_unidad(10.0, 10.0, 10.0, UnidadLongitud::Mm)),
+        ];
+        let envio = Envio::desde_items(&items);
+
+        assert_eq!(envio.peso_kg, 4.0);
+        assert!((envio.volumen_cm3 - 2001.0).abs() < 1e-9);
+        assert_eq!(envio.como_paquete().peso_kg, 4.0);
+        assert!((envio.como_paquete().volumen_cm3() - 2001.0).abs() < 1e-6);
+
+        let servicio = servicio(5.0, 1.0, None);
+        assert_eq!(servicio.calcular_costo_envio(&envio), 9.0);
+    }
+}
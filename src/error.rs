@@ -0,0 +1,113 @@
+use std::error::Error;
+use std::{fmt, io};
+
+use crate::Moneda;
+
+/// Errores que pueden surgir al comparar o calcular costos de envío.
+#[derive(Debug, PartialEq)]
+pub enum OptimizadorError {
+    /// No hay tasa de cambio registrada para convertir entre dos monedas.
+    TasaNoDisponible { origen: Moneda, destino: Moneda },
+    /// Un parámetro de entrada está fuera del rango aceptado.
+    ParametroInvalido(String),
+    /// La zona de destino no está en la tabla de zonas y no hay factor por defecto.
+    ZonaDesconocida(String),
+    /// El paquete no cabe en ninguna de las cajas estándar del servicio.
+    SinCajaEstandar { servicio: String },
+    /// Filas de un CSV de paquetes que no se pudieron interpretar, en orden.
+    FilasInvalidas(Vec<FilaInvalida>),
+    /// Falló la lectura o la escritura; guarda el mensaje del error de E/S.
+    Io(String),
+}
+
+impl fmt::Display for OptimizadorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptimizadorError::TasaNoDisponible { origen, destino } => {
+                write!(f, "No hay tasa de cambio de {} a {}", origen, destino)
+            }
+            OptimizadorError::ParametroInvalido(motivo) => write!(f, "Parámetro inválido: {}", motivo),
+            OptimizadorError::ZonaDesconocida(zona) => write!(f, "La zona {} no está en la tabla de zonas", zona),
+            OptimizadorError::SinCajaEstandar { servicio } => {
+                write!(f, "El paquete no cabe en ninguna caja estándar de {}", servicio)
+            }
+            OptimizadorError::FilasInvalidas(filas) => {
+                let detalle: Vec<String> = filas.iter().map(ToString::to_string).collect();
+                write!(f, "Filas inválidas: {}", detalle.join("; "))
+            }
+            OptimizadorError::Io(mensaje) => write!(f, "Error de entrada/salida: {}", mensaje),
+        }
+    }
+}
+
+impl Error for OptimizadorError {}
+
+/// Fila de un CSV que no se pudo interpretar. `linea` empieza en 1 y cuenta el
+/// encabezado.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilaInvalida {
+    pub linea: usize,
+    pub motivo: String,
+}
+
+impl fmt::Display for FilaInvalida {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "línea {}: {}", self.linea, self.motivo)
+    }
+}
+
+impl From<io::Error> for OptimizadorError {
+    fn from(error: io::Error) -> Self {
+        OptimizadorError::Io(error.to_string())
+    }
+}
+
+/// Errores al cargar datos (paquetes, catálogos) desde una fuente externa.
+#[derive(Debug)]
+pub enum CargaError {
+    /// La entrada no contenía datos.
+    EntradaVacia,
+    /// La entrada no es JSON válido o no tiene la forma esperada.
+    JsonInvalido(serde_json::Error),
+    /// Falló la lectura de la entrada.
+    Io(io::Error),
+    /// El catálogo declara una versión de formato más nueva que la soportada.
+    VersionNoSoportada { encontrada: u32, soportada: u32 },
+}
+
+impl fmt::Display for CargaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CargaError::EntradaVacia => write!(f, "La entrada está vacía"),
+            CargaError::JsonInvalido(error) => write!(f, "JSON inválido: {}", error),
+            CargaError::Io(error) => write!(f, "Error de lectura: {}", error),
+            CargaError::VersionNoSoportada { encontrada, soportada } => write!(
+                f,
+                "El catálogo usa la versión de formato {}, pero sólo se soporta hasta la {}",
+                encontrada, soportada
+            ),
+        }
+    }
+}
+
+impl Error for CargaError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CargaError::EntradaVacia | CargaError::VersionNoSoportada { .. } => None,
+            CargaError::JsonInvalido(error) => Some(error),
+            CargaError::Io(error) => Some(error),
+        }
+    }
+}
+
+impl From<serde_json::Error> for CargaError {
+    fn from(error: serde_json::Error) -> Self {
+        CargaError::JsonInvalido(error)
+    }
+}
+
+impl From<io::Error> for CargaError {
+    fn from(error: io::Error) -> Self {
+        CargaError::Io(error)
+    }
+}
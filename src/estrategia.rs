@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use crate::{Paquete, ServicioDeMensajeria};
+
+/// Estrategia de cálculo de costo intercambiable en tiempo de ejecución.
+pub trait EstrategiaDeCosto {
+    /// Calcula el costo de enviar un paquete con esta estrategia.
+    fn calcular_costo(&self, paquete: &Paquete) -> f64;
+}
+
+impl EstrategiaDeCosto for ServicioDeMensajeria {
+    fn calcular_costo(&self, paquete: &Paquete) -> f64 {
+        ServicioDeMensajeria::calcular_costo(self, paquete)
+    }
+}
+
+/// Servicio cuyo costo se delega a una estrategia registrada.
+pub struct ServicioDinamico<'a> {
+    pub nombre: String,
+    pub estrategia: &'a dyn EstrategiaDeCosto,
+}
+
+impl ServicioDinamico<'_> {
+    /// Calcula el costo de envío usando la estrategia del servicio.
+    pub fn calcular_costo(&self, paquete: &Paquete) -> f64 {
+        self.estrategia.calcular_costo(paquete)
+    }
+}
+
+/// Registro que asocia un identificador de texto con una estrategia de costo,
+/// para que las configuraciones puedan referirse a estrategias por nombre.
+#[derive(Default)]
+pub struct RegistroEstrategias {
+    estrategias: HashMap<String, Box<dyn EstrategiaDeCosto>>,
+}
+
+impl RegistroEstrategias {
+    /// Crea un registro vacío.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra una estrategia bajo `clave`, devolviendo la que hubiera antes.
+    pub fn registrar(
+        &mut self,
+        clave: impl Into<String>,
+        estrategia: Box<dyn EstrategiaDeCosto>,
+    ) -> Option<Box<dyn EstrategiaDeCosto>> {
+        self.estrategias.insert(clave.into(), estrategia)
+    }
+
+    /// Obtiene la estrategia registrada bajo `clave`.
+    pub fn obtener(&self, clave: &str) -> Option<&dyn EstrategiaDeCosto> {
+        self.estrategias.get(clave).map(|estrategia| estrategia.as_ref())
+    }
+
+    /// Construye un servicio llamado `nombre` que usa la estrategia `clave`.
+    pub fn construir_servicio(&self, nombre: impl Into<String>, clave: &str) -> Option<ServicioDinamico<'_>> {
+        self.obtener(clave).map(|estrategia| ServicioDinamico {
+            nombre: nombre.into(),
+            estrategia,
+        })
+    }
+}
+
+/// Compara un catálogo de servicios dinámicos y recuerda el ranking del
+/// último paquete consultado.
+///
+/// Una consulta con el mismo peso, dimensiones y `es_peligroso` que la
+/// anterior devuelve el ranking guardado sin recalcular costos; el `id` del
+/// paquete no cuenta porque no cambia el costo. Cualquier cambio en el
+/// catálogo invalida la caché.
+pub struct ComparadorCacheado<'a> {
+    servicios: Vec<ServicioDinamico<'a>>,
+    cache: Option<(Paquete, Vec<(String, f64)>)>,
+}
+
+impl<'a> ComparadorCacheado<'a> {
+    /// Crea un comparador para el catálogo dado.
+    pub fn new(servicios: Vec<ServicioDinamico<'a>>) -> Self {
+        ComparadorCacheado { servicios, cache: None }
+    }
+
+    /// Agrega un servicio al catálogo e invalida la caché.
+    pub fn agregar_servicio(&mut self, servicio: ServicioDinamico<'a>) {
+        self.servicios.push(servicio);
+        self.cache = None;
+    }
+
+    /// Reemplaza el catálogo completo e invalida la caché.
+    pub fn reemplazar_catalogo(&mut self, servicios: Vec<ServicioDinamico<'a>>) {
+        self.servicios = servicios;
+        self.cache = None;
+    }
+
+    /// Devuelve pares `(servicio, costo)` ordenados del más barato al más caro.
+    pub fn comparar(&mut self, paquete: &Paquete) -> &[(String, f64)] {
+        let vigente = matches!(
+            &self.cache,
+            Some((anterior, _)) if anterior.peso_kg == paquete.peso_kg
+                && anterior.dimensiones == paquete.dimensiones
+                && anterior.es_peligroso == paquete.es_peligroso
+        );
+        if !vigente {
+            let mut ranking: Vec<(String, f64)> = self
+                .servicios
+                .iter()
+                .map(|servicio| (servicio.nombre.clone(), servicio.calcular_costo(paquete)))
+                .collect();
+            ranking.sort_by(|a, b| a.1.total_cmp(&b.1));
+            self.cache = Some((paquete.clone(), ranking));
+        }
+        self.cache.as_ref().map(|(_, ranking)| ranking.as_slice()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::{Dimensiones, Tarifa};
+
+    /// Cobra un monto fijo sin importar el paquete.
+    struct TarifaPlana(f64);
+
+    impl EstrategiaDeCosto for TarifaPlana {
+        fn calcular_costo(&self, _paquete: &Paquete) -> f64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_registro_resuelve_estrategia_por_clave() {
+        let mut registro = RegistroEstrategias::new();
+        registro.registrar("plana", Box::new(TarifaPlana(7.5)));
+        registro.registrar(
+            "lineal",
+            Box::new(ServicioDeMensajeria {
+                nombre: "Lineal".to_string(),
+                tarifa: Tarifa { costo_base: 1.0, costo_por_kg: 2.0, costo_por_volumen_cm3: 0.0, ..Default::default() },
+                ..Default::default()
+            }),
+        );
+        let paquete = Paquete::new(3.0, Dimensiones::new(10.0, 10.0, 10.0));
+
+        let servicio = registro.construir_servicio("Mensajería Plana", "plana").unwrap();
+        assert_eq!(servicio.nombre, "Mensajería Plana");
+        assert_eq!(servicio.calcular_costo(&paquete), 7.5);
+        assert_eq!(registro.obtener("lineal").unwrap().calcular_costo(&paquete), 7.0);
+        assert!(registro.construir_servicio("Fantasma", "desconocida").is_none());
+    }
+
+    /// Cobra un monto fijo y cuenta cuántas veces se le pide un costo.
+    struct TarifaContada {
+        monto: f64,
+        llamadas: Cell<usize>,
+    }
+
+    impl EstrategiaDeCosto for TarifaContada {
+        fn calcular_costo(&self, _paquete: &Paquete) -> f64 {
+            self.llamadas.set(self.llamadas.get() + 1);
+            self.monto
+        }
+    }
+
+    #[test]
+    fn test_comparador_cacheado_no_recalcula_consulta_repetida() {
+        let cara = TarifaContada { monto: 12.0, llamadas: Cell::new(0) };
+        let barata = TarifaContada { monto: 8.0, llamadas: Cell::new(0) };
+        let mut comparador = ComparadorCacheado::new(vec![
+            ServicioDinamico { nombre: "Cara".to_string(), estrategia: &cara },
+            ServicioDinamico { nombre: "Barata".to_string(), estrategia: &barata },
+        ]);
+        let paquete = Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0));
+
+        let esperado = vec![("Barata".to_string(), 8.0), ("Cara".to_string(), 12.0)];
+        assert_eq!(comparador.comparar(&paquete), esperado.as_slice());
+        assert_eq!(comparador.comparar(&paquete), esperado.as_slice());
+        assert_eq!(cara.llamadas.get(), 1);
+
+        let con_referencia = Paquete { id: Some("PED-1".to_string()), ..paquete.clone() };
+        assert_eq!(comparador.comparar(&con_referencia), esperado.as_slice());
+        assert_eq!(cara.llamadas.get(), 1);
+
+        let otro = Paquete { peso_kg: 2.0, ..paquete.clone() };
+        comparador.comparar(&otro);
+        assert_eq!(cara.llamadas.get(), 2);
+
+        let nueva = TarifaContada { monto: 5.0, llamadas: Cell::new(0) };
+        comparador.agregar_servicio(ServicioDinamico { nombre: "Nueva".to_string(), estrategia: &nueva });
+        assert_eq!(comparador.comparar(&otro)[0], ("Nueva".to_string(), 5.0));
+        assert_eq!(cara.llamadas.get(), 3);
+    }
+}
@@ -0,0 +1,161 @@
+use std::fmt::Write;
+
+use crate::{Dimensiones, Paquete, ResumenLote, ServicioDeMensajeria};
+
+/// Arma una tabla de texto con el costo de cada servicio para un paquete.
+///
+/// Con `mostrar_peso_facturable` se agrega una columna con el peso que cobra
+/// cada servicio e indica si es el real o el volumétrico, lo que explica por
+/// qué una caja grande y liviana resulta cara.
+pub fn tabla_comparativa(
+    servicios: &[ServicioDeMensajeria],
+    paquete: &Paquete,
+    mostrar_peso_facturable: bool,
+) -> String {
+    let ancho_nombre = servicios
+        .iter()
+        .map(|servicio| servicio.nombre.chars().count())
+        .chain(["Servicio".len()])
+        .max()
+        .unwrap_or_default();
+
+    let mut tabla = format!("{:<ancho_nombre$} | {:>10}", "Servicio", "Costo");
+    if mostrar_peso_facturable {
+        tabla.push_str(" | Peso facturable");
+    }
+    tabla.push('\n');
+
+    for servicio in servicios {
+        let costo = format!("${:.2}", servicio.calcular_costo(paquete));
+        let _ = write!(tabla, "{:<ancho_nombre$} | {:>10}", servicio.nombre, costo);
+        if mostrar_peso_facturable {
+            let peso_facturable = servicio.peso_facturable(paquete);
+            let origen = if peso_facturable > paquete.peso_kg { "volumétrico" } else { "real" };
+            let _ = write!(tabla, " | {:.2} kg ({})", peso_facturable, origen);
+        }
+        tabla.push('\n');
+    }
+    tabla
+}
+
+/// Arma la tarjeta de precios de un servicio para cajas estándar, dadas como
+/// `(nombre, dimensiones, peso_kg)`, ordenada de la más barata a la más cara.
+pub fn tarjeta_de_precios(servicio: &ServicioDeMensajeria, cajas: &[(String, Dimensiones, f64)]) -> Vec<(String, f64)> {
+    let mut tarjeta: Vec<(String, f64)> = cajas
+        .iter()
+        .map(|(nombre, dimensiones, peso_kg)| {
+            let paquete = Paquete::new(*peso_kg, *dimensiones);
+            (nombre.clone(), servicio.calcular_costo(&paquete))
+        })
+        .collect();
+    tarjeta.sort_by(|a, b| a.1.total_cmp(&b.1));
+    tarjeta
+}
+
+/// Expone el resumen de un lote en el formato de texto de Prometheus, para que
+/// un servicio que use la biblioteca pueda publicarlo como métricas.
+pub fn metricas_texto(resumen: &ResumenLote) -> String {
+    let metricas = [
+        ("envio_paquetes_cotizados", "Paquetes del lote con alguna opción de envío.", resumen.cantidad as f64),
+        ("envio_costo_total", "Suma del costo ganador de cada paquete.", resumen.total),
+        ("envio_costo_promedio", "Costo ganador promedio por paquete.", resumen.promedio),
+        ("envio_costo_minimo", "Menor costo ganador del lote.", resumen.minimo),
+        ("envio_costo_maximo", "Mayor costo ganador del lote.", resumen.maximo),
+    ];
+
+    let mut texto = String::new();
+    for (nombre, ayuda, valor) in metricas {
+        let _ = writeln!(texto, "# HELP {} {}", nombre, ayuda);
+        let _ = writeln!(texto, "# TYPE {} gauge", nombre);
+        let _ = writeln!(texto, "{} {}", nombre, valor);
+    }
+    texto
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dimensiones, Tarifa};
+
+    fn servicios() -> Vec<ServicioDeMensajeria> {
+        vec![
+            ServicioDeMensajeria {
+                nombre: "Por peso real".to_string(),
+                tarifa: Tarifa { costo_base: 5.0, costo_por_kg: 1.0, ..Default::default() },
+                ..Default::default()
+            },
+            ServicioDeMensajeria {
+                nombre: "Aéreo".to_string(),
+                tarifa: Tarifa {
+                    costo_base: 5.0,
+                    costo_por_kg: 1.0,
+                    divisor_volumetrico: Some(5000.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn caja_grande_liviana() -> Paquete {
+        // 50000 cm³ / 5000 = 10 kg volumétricos frente a 2 kg reales.
+        Paquete::new(2.0, Dimensiones::new(50.0, 50.0, 20.0))
+    }
+
+    #[test]
+    fn test_tabla_muestra_peso_facturable() {
+        let tabla = tabla_comparativa(&servicios(), &caja_grande_liviana(), true);
+        let lineas: Vec<&str> = tabla.lines().collect();
+
+        assert_eq!(lineas[0], "Servicio      |      Costo | Peso facturable");
+        assert_eq!(lineas[1], "Por peso real |      $7.00 | 2.00 kg (real)");
+        assert_eq!(lineas[2], "Aéreo         |     $15.00 | 10.00 kg (volumétrico)");
+    }
+
+    #[test]
+    fn test_tabla_sin_peso_facturable() {
+        let tabla = tabla_comparativa(&servicios(), &caja_grande_liviana(), false);
+        assert!(!tabla.contains("Peso facturable"));
+        assert_eq!(tabla.lines().nth(2), Some("Aéreo         |     $15.00"));
+    }
+
+    #[test]
+    fn test_metricas_texto() {
+        let resumen = ResumenLote { cantidad: 4, total: 49.36, promedio: 12.34, minimo: 3.5, maximo: 20.0 };
+        let texto = metricas_texto(&resumen);
+
+        for linea in [
+            "envio_paquetes_cotizados 4",
+            "envio_costo_total 49.36",
+            "envio_costo_promedio 12.34",
+            "envio_costo_minimo 3.5",
+            "envio_costo_maximo 20",
+            "# TYPE envio_costo_promedio gauge",
+        ] {
+            assert!(texto.lines().any(|l| l == linea), "falta la línea {:?}", linea);
+        }
+    }
+
+    #[test]
+    fn test_tarjeta_de_precios_ordenada() {
+        let servicio = ServicioDeMensajeria {
+            nombre: "Rappi Courier".to_string(),
+            tarifa: Tarifa { costo_base: 5.0, costo_por_kg: 1.5, costo_por_volumen_cm3: 0.001, ..Default::default() },
+            ..Default::default()
+        };
+        let cajas = vec![
+            ("M".to_string(), Dimensiones::new(30.0, 20.0, 20.0), 3.0),
+            ("L".to_string(), Dimensiones::new(50.0, 40.0, 30.0), 8.0),
+            ("S".to_string(), Dimensiones::new(20.0, 15.0, 10.0), 1.0),
+        ];
+
+        let tarjeta = tarjeta_de_precios(&servicio, &cajas);
+        let nombres: Vec<&str> = tarjeta.iter().map(|(nombre, _)| nombre.as_str()).collect();
+        assert_eq!(nombres, vec!["S", "M", "L"]);
+        // S: 5 + 1.5 + 3000 * 0.001; M: 5 + 4.5 + 12; L: 5 + 12 + 60.
+        let costos: Vec<f64> = tarjeta.iter().map(|&(_, costo)| costo).collect();
+        for (costo, esperado) in costos.into_iter().zip([9.5, 21.5, 77.0]) {
+            assert!((costo - esperado).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,425 @@
+use std::collections::HashMap;
+
+use crate::{OpcionDeEnvio, Paquete, ServicioDeMensajeria, mejor_opcion, servicio_mas_barato};
+
+/// Recorre un lote de paquetes y devuelve la opción más barata encontrada en
+/// todo el lote.
+///
+/// Devuelve `None` si el lote está vacío o el catálogo no tiene servicios.
+pub fn mas_barata_en_lote<'a>(
+    servicios: &'a [ServicioDeMensajeria],
+    paquetes: impl IntoIterator<Item = Paquete>,
+) -> Option<OpcionDeEnvio<'a>> {
+    let mut mas_barata: Option<OpcionDeEnvio<'a>> = None;
+
+    for paquete in paquetes {
+        if let Some(opcion) = mejor_opcion(servicios, &paquete)
+            && mas_barata.as_ref().is_none_or(|actual| opcion.costo < actual.costo)
+        {
+            mas_barata = Some(opcion);
+        }
+    }
+    mas_barata
+}
+
+/// Servicio asignado a un paquete de un lote.
+#[derive(Debug)]
+pub struct AsignacionPaquete<'a> {
+    /// Referencia del paquete, copiada de `Paquete::id`.
+    pub id: Option<String>,
+    /// Opción más barata, o `None` si ningún servicio admite el paquete.
+    pub opcion: Option<OpcionDeEnvio<'a>>,
+}
+
+/// Asigna a cada paquete del lote su opción más barata, en el mismo orden y
+/// conservando su `id` para asociar el resultado con el pedido de origen.
+pub fn asignacion_optima_por_paquete<'a>(
+    servicios: &'a [ServicioDeMensajeria],
+    paquetes: impl IntoIterator<Item = Paquete>,
+) -> Vec<AsignacionPaquete<'a>> {
+    paquetes
+        .into_iter()
+        .map(|paquete| AsignacionPaquete {
+            opcion: mejor_opcion(servicios, &paquete),
+            id: paquete.id,
+        })
+        .collect()
+}
+
+/// Agregados del costo ganador de cada paquete de un lote.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResumenLote {
+    /// Paquetes que tuvieron alguna opción de envío.
+    pub cantidad: usize,
+    pub total: f64,
+    pub promedio: f64,
+    pub minimo: f64,
+    pub maximo: f64,
+}
+
+/// Resume el costo de la opción más barata de cada paquete del lote.
+///
+/// Los paquetes sin servicio disponible no cuentan; si ninguno tiene opción,
+/// devuelve `None`.
+pub fn resumen_lote(
+    servicios: &[ServicioDeMensajeria],
+    paquetes: impl IntoIterator<Item = Paquete>,
+) -> Option<ResumenLote> {
+    plegar_envios(servicios, paquetes.into_iter())
+}
+
+/// Versión en flujo de `resumen_lote`: consume los paquetes de a uno, sin
+/// reunirlos en memoria, por lo que sirve para recorrer un cursor de base de
+/// datos o cualquier fuente de tamaño desconocido.
+pub fn plegar_envios<I: Iterator<Item = Paquete>>(servicios: &[ServicioDeMensajeria], paquetes: I) -> Option<ResumenLote> {
+    resumir_costos(paquetes.filter_map(|paquete| mejor_opcion(servicios, &paquete).map(|opcion| opcion.costo)))
+}
+
+/// Resume lo que costaría cada servicio si llevara todo el lote, no sólo los
+/// paquetes en los que gana, indexado por nombre de servicio.
+///
+/// Cada resumen cuenta los paquetes que el servicio admite; los servicios que
+/// no admiten ninguno no aparecen.
+pub fn estadisticas_por_servicio(
+    servicios: &[ServicioDeMensajeria],
+    paquetes: &[Paquete],
+) -> HashMap<String, ResumenLote> {
+    servicios
+        .iter()
+        .filter_map(|servicio| {
+            let costos = paquetes
+                .iter()
+                .filter(|paquete| servicio.admite(paquete))
+                .map(|paquete| servicio.calcular_costo(paquete));
+            Some((servicio.nombre.clone(), resumir_costos(costos)?))
+        })
+        .collect()
+}
+
+/// Agrega una secuencia de costos; devuelve `None` si está vacía.
+fn resumir_costos(costos: impl Iterator<Item = f64>) -> Option<ResumenLote> {
+    let resumen = costos.fold(None, |resumen: Option<ResumenLote>, costo| {
+        Some(match resumen {
+            None => ResumenLote { cantidad: 1, total: costo, promedio: 0.0, minimo: costo, maximo: costo },
+            Some(actual) => ResumenLote {
+                cantidad: actual.cantidad + 1,
+                total: actual.total + costo,
+                minimo: actual.minimo.min(costo),
+                maximo: actual.maximo.max(costo),
+                ..actual
+            },
+        })
+    });
+
+    resumen.map(|resumen| ResumenLote { promedio: resumen.total / resumen.cantidad as f64, ..resumen })
+}
+
+/// Costo total de una operación de almacén: la suma del envío más barato de
+/// cada paquete entrante y saliente.
+///
+/// Devuelve `None` si algún paquete no tiene servicio disponible, para no
+/// subestimar el total.
+pub fn costo_total_operacion(
+    servicios: &[ServicioDeMensajeria],
+    entrantes: &[Paquete],
+    salientes: &[Paquete],
+) -> Option<f64> {
+    entrantes
+        .iter()
+        .chain(salientes)
+        .map(|paquete| mejor_opcion(servicios, paquete).map(|opcion| opcion.costo))
+        .sum()
+}
+
+/// Encuentra el paquete con la mayor diferencia entre su servicio más barato
+/// y el más caro, devolviendo `(índice, diferencia)`: la mayor oportunidad de
+/// ahorro del lote.
+///
+/// Sólo cuentan los servicios que admiten cada paquete; ante la misma
+/// diferencia gana el primero. Devuelve `None` si ningún paquete tiene opción.
+pub fn mayor_ahorro(servicios: &[ServicioDeMensajeria], paquetes: &[Paquete]) -> Option<(usize, f64)> {
+    let mut mayor: Option<(usize, f64)> = None;
+
+    for (indice, paquete) in paquetes.iter().enumerate() {
+        let costos = servicios
+            .iter()
+            .filter(|servicio| servicio.admite(paquete))
+            .map(|servicio| servicio.calcular_costo(paquete));
+        let Some((minimo, maximo)) = costos.fold(None, |rango: Option<(f64, f64)>, costo| {
+            Some(rango.map_or((costo, costo), |(minimo, maximo)| (minimo.min(costo), maximo.max(costo))))
+        }) else {
+            continue;
+        };
+        let diferencia = maximo - minimo;
+        if mayor.is_none_or(|(_, actual)| diferencia > actual) {
+            mayor = Some((indice, diferencia));
+        }
+    }
+    mayor
+}
+
+/// Ahorro total del lote si se sumara `nuevo` al catálogo: en cada paquete
+/// donde el nuevo servicio sería más barato que la mejor opción actual, la
+/// diferencia entre ambos.
+///
+/// Los paquetes que hoy ningún servicio admite no suman, aunque el nuevo los
+/// admita, porque no hay un costo actual contra el cual medir el ahorro.
+pub fn impacto_nuevo_servicio(
+    servicios: &[ServicioDeMensajeria],
+    nuevo: &ServicioDeMensajeria,
+    paquetes: &[Paquete],
+) -> f64 {
+    paquetes
+        .iter()
+        .filter(|paquete| nuevo.admite(paquete))
+        .filter_map(|paquete| {
+            let actual = mejor_opcion(servicios, paquete)?.costo;
+            Some((actual - nuevo.calcular_costo(paquete)).max(0.0))
+        })
+        .sum()
+}
+
+/// Plazo de entrega esperado del lote, en días: el promedio de los
+/// `dias_estimados` del servicio más barato de cada paquete, que es el que el
+/// cliente elegiría.
+///
+/// El promedio se toma sólo sobre los paquetes con plazo conocido: se omiten
+/// los que ningún servicio admite y aquellos cuyo servicio más barato no
+/// publica `dias_estimados`, aunque otro más caro sí lo haga. Devuelve `None`
+/// si no queda ningún paquete con plazo.
+pub fn tiempo_esperado_entrega(servicios: &[ServicioDeMensajeria], paquetes: &[Paquete]) -> Option<f64> {
+    let dias: Vec<u32> = paquetes
+        .iter()
+        .filter_map(|paquete| servicio_mas_barato(servicios, paquete)?.0.dias_estimados)
+        .collect();
+    if dias.is_empty() {
+        return None;
+    }
+    Some(dias.iter().map(|&dias| dias as f64).sum::<f64>() / dias.len() as f64)
+}
+
+/// Calcula los percentiles pedidos sobre el costo ganador de cada paquete,
+/// devolviendo pares `(percentil, costo)` en el orden solicitado.
+///
+/// Los percentiles se expresan de 0 a 100 y se recortan a ese rango. Se
+/// interpola linealmente entre los dos costos ordenados más cercanos a la
+/// posición `p / 100 * (n - 1)`, el mismo método que usan por defecto NumPy y
+/// las hojas de cálculo. Los paquetes sin servicio disponible no cuentan; si
+/// ninguno tiene opción, el resultado está vacío.
+pub fn resumen_percentiles(
+    servicios: &[ServicioDeMensajeria],
+    paquetes: &[Paquete],
+    percentiles: &[f64],
+) -> Vec<(f64, f64)> {
+    let mut costos = costos_ganadores(servicios, paquetes);
+    if costos.is_empty() {
+        return Vec::new();
+    }
+    costos.sort_by(f64::total_cmp);
+
+    let ultimo = (costos.len() - 1) as f64;
+    percentiles
+        .iter()
+        .map(|&percentil| {
+            let posicion = percentil.clamp(0.0, 100.0) / 100.0 * ultimo;
+            let inferior = costos[posicion.floor() as usize];
+            let superior = costos[posicion.ceil() as usize];
+            (percentil, inferior + (superior - inferior) * posicion.fract())
+        })
+        .collect()
+}
+
+/// Costo de la opción más barata de cada paquete que tenga alguna.
+fn costos_ganadores(servicios: &[ServicioDeMensajeria], paquetes: &[Paquete]) -> Vec<f64> {
+    paquetes
+        .iter()
+        .filter_map(|paquete| mejor_opcion(servicios, paquete))
+        .map(|opcion| opcion.costo)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dimensiones, Tarifa};
+
+    fn servicios() -> Vec<ServicioDeMensajeria> {
+        vec![ServicioDeMensajeria {
+            nombre: "Único".to_string(),
+            tarifa: Tarifa { costo_base: 1.0, costo_por_kg: 1.0, costo_por_volumen_cm3: 0.0, ..Default::default() },
+            ..Default::default()
+        }]
+    }
+
+    fn paquete(peso_kg: f64) -> Paquete {
+        Paquete::new(peso_kg, Dimensiones::new(10.0, 10.0, 10.0))
+    }
+
+    #[test]
+    fn test_lote_vacio_devuelve_none() {
+        assert!(mas_barata_en_lote(&servicios(), Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_lote_devuelve_paquete_mas_barato() {
+        let servicios = servicios();
+        let mejor = mas_barata_en_lote(&servicios, vec![paquete(4.0), paquete(2.0), paquete(3.0)]).unwrap();
+        assert_eq!(mejor.servicio, "Único");
+        assert_eq!(mejor.costo, 3.0);
+    }
+
+    #[test]
+    fn test_asignacion_conserva_id_del_paquete() {
+        let paquetes = vec![
+            Paquete { id: Some("PED-001".to_string()), ..paquete(2.0) },
+            paquete(3.0),
+            Paquete { id: Some("PED-003".to_string()), ..paquete(1.0) },
+        ];
+        let servicios = servicios();
+        let asignaciones = asignacion_optima_por_paquete(&servicios, paquetes);
+
+        let resumen: Vec<(Option<&str>, f64)> = asignaciones
+            .iter()
+            .map(|asignacion| (asignacion.id.as_deref(), asignacion.opcion.as_ref().unwrap().costo))
+            .collect();
+        assert_eq!(resumen, vec![(Some("PED-001"), 3.0), (None, 4.0), (Some("PED-003"), 2.0)]);
+    }
+
+    #[test]
+    fn test_resumen_lote() {
+        let paquetes = [4.0, 2.0, 3.0, 7.0].into_iter().map(paquete);
+        let resumen = resumen_lote(&servicios(), paquetes).unwrap();
+        assert_eq!(
+            resumen,
+            ResumenLote { cantidad: 4, total: 20.0, promedio: 5.0, minimo: 3.0, maximo: 8.0 }
+        );
+        assert!(resumen_lote(&servicios(), Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_impacto_nuevo_servicio_captura_los_pesados() {
+        // El nuevo cobra 6 fijos: gana en los paquetes de más de 5 kg.
+        let nuevo = ServicioDeMensajeria {
+            nombre: "Tarifa plana".to_string(),
+            tarifa: Tarifa { costo_base: 6.0, ..Default::default() },
+            ..Default::default()
+        };
+        let paquetes = [1.0, 8.0, 4.0, 12.0].map(paquete);
+
+        assert_eq!(impacto_nuevo_servicio(&servicios(), &nuevo, &paquetes), 3.0 + 7.0);
+        assert_eq!(impacto_nuevo_servicio(&servicios(), &nuevo, &paquetes[..1]), 0.0);
+    }
+
+    #[test]
+    fn test_tiempo_esperado_entrega_segun_la_eleccion() {
+        // Hasta 4 kg gana el terrestre (4 días); desde ahí, el plano (1 día).
+        let servicio = |nombre: &str, costo_base, costo_por_kg, dias| ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base, costo_por_kg, ..Default::default() },
+            dias_estimados: Some(dias),
+            ..Default::default()
+        };
+        let con_plazo = vec![servicio("Terrestre", 1.0, 1.0, 4), servicio("Plano", 5.0, 0.0, 1)];
+        let paquetes = [1.0, 2.0, 6.0, 3.0, 9.0].map(paquete);
+
+        assert_eq!(tiempo_esperado_entrega(&con_plazo, &paquetes), Some((4.0 * 3.0 + 1.0 * 2.0) / 5.0));
+        assert_eq!(tiempo_esperado_entrega(&servicios(), &paquetes), None);
+
+        // Con nombres repetidos cuenta el plazo del servicio que ganó, no el primero con ese nombre.
+        let homonimos = vec![servicio("Olva", 5.0, 0.0, 3), servicio("Olva", 1.0, 0.0, 2)];
+        assert_eq!(tiempo_esperado_entrega(&homonimos, &paquetes), Some(2.0));
+    }
+
+    #[test]
+    fn test_estadisticas_por_servicio_cuentan_todo_el_lote() {
+        let mut servicios = servicios();
+        servicios.push(ServicioDeMensajeria {
+            nombre: "Liviano".to_string(),
+            tarifa: Tarifa { costo_base: 3.0, ..Default::default() },
+            peso_maximo_kg: Some(3.0),
+            ..Default::default()
+        });
+        servicios.push(ServicioDeMensajeria {
+            nombre: "Sólo pallets".to_string(),
+            peso_maximo_kg: Some(0.5),
+            ..Default::default()
+        });
+        let paquetes = [4.0, 2.0, 3.0, 7.0].map(paquete);
+
+        let estadisticas = estadisticas_por_servicio(&servicios, &paquetes);
+        assert_eq!(estadisticas.len(), 2);
+        assert_eq!(
+            estadisticas["Único"],
+            ResumenLote { cantidad: 4, total: 20.0, promedio: 5.0, minimo: 3.0, maximo: 8.0 }
+        );
+        assert_eq!(
+            estadisticas["Liviano"],
+            ResumenLote { cantidad: 2, total: 6.0, promedio: 3.0, minimo: 3.0, maximo: 3.0 }
+        );
+    }
+
+    #[test]
+    fn test_plegar_envios_sin_reunir_el_flujo() {
+        // Un flujo perezoso de 200 000 paquetes que nunca se guarda en memoria.
+        let mut generados = 0;
+        let flujo = std::iter::repeat_with(|| {
+            generados += 1;
+            paquete(1.0 + (generados % 10) as f64)
+        })
+        .take(200_000);
+
+        let resumen = plegar_envios(&servicios(), flujo).unwrap();
+        assert_eq!(resumen.cantidad, 200_000);
+        assert_eq!(resumen.minimo, 2.0);
+        assert_eq!(resumen.maximo, 11.0);
+        assert!((resumen.promedio - 6.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_costo_total_operacion() {
+        let entrantes = [paquete(10.0), paquete(4.0)];
+        let salientes = [paquete(1.0), paquete(2.0), paquete(0.5)];
+        // Entrantes: 11 + 5; salientes: 2 + 3 + 1.5.
+        assert_eq!(costo_total_operacion(&servicios(), &entrantes, &salientes), Some(22.5));
+        assert_eq!(costo_total_operacion(&[], &entrantes, &salientes), None);
+    }
+
+    #[test]
+    fn test_mayor_ahorro() {
+        let servicios = vec![
+            ServicioDeMensajeria {
+                nombre: "Por kg".to_string(),
+                tarifa: Tarifa { costo_base: 1.0, costo_por_kg: 1.0, ..Default::default() },
+                ..Default::default()
+            },
+            ServicioDeMensajeria {
+                nombre: "Por kg caro".to_string(),
+                tarifa: Tarifa { costo_base: 2.0, costo_por_kg: 4.0, ..Default::default() },
+                ..Default::default()
+            },
+        ];
+        // Diferencias: 1 + 3 * peso, así que el de 20 kg sobresale con 61.
+        let paquetes: Vec<Paquete> = [2.0, 20.0, 1.0].into_iter().map(paquete).collect();
+        assert_eq!(mayor_ahorro(&servicios, &paquetes), Some((1, 61.0)));
+        assert_eq!(mayor_ahorro(&[], &paquetes), None);
+    }
+
+    #[test]
+    fn test_resumen_percentiles_interpola() {
+        // Costos ganadores: 2, 3, 4, 5, 6.
+        let paquetes: Vec<Paquete> = [5.0, 1.0, 3.0, 2.0, 4.0].into_iter().map(paquete).collect();
+        let resumen = resumen_percentiles(&servicios(), &paquetes, &[50.0, 90.0, 99.0, 0.0]);
+
+        let esperados = [(50.0, 4.0), (90.0, 5.6), (99.0, 5.96), (0.0, 2.0)];
+        assert_eq!(resumen.len(), esperados.len());
+        for ((percentil, costo), (percentil_esperado, costo_esperado)) in resumen.into_iter().zip(esperados) {
+            assert_eq!(percentil, percentil_esperado);
+            assert!((costo - costo_esperado).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_resumen_percentiles_lote_vacio() {
+        assert!(resumen_percentiles(&servicios(), &[], &[50.0]).is_empty());
+    }
+}
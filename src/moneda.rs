@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{OpcionDeEnvio, OptimizadorError, Paquete, ServicioDeMensajeria};
+
+/// Monedas en las que un servicio puede expresar su tarifa.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Moneda {
+    Usd,
+    Eur,
+    Pen,
+    Cop,
+    Mxn,
+    Clp,
+}
+
+impl fmt::Display for Moneda {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let codigo = match self {
+            Moneda::Usd => "USD",
+            Moneda::Eur => "EUR",
+            Moneda::Pen => "PEN",
+            Moneda::Cop => "COP",
+            Moneda::Mxn => "MXN",
+            Moneda::Clp => "CLP",
+        };
+        write!(f, "{}", codigo)
+    }
+}
+
+impl Moneda {
+    /// Cantidad de decimales con que se expresan los montos en esta moneda,
+    /// según ISO 4217.
+    pub fn decimales(&self) -> i32 {
+        match self {
+            Moneda::Clp => 0,
+            _ => 2,
+        }
+    }
+}
+
+impl OpcionDeEnvio<'_> {
+    /// Precio en la moneda de origen y en la moneda `a`, convertido con
+    /// `tasa` (unidades de `a` por unidad de origen), para cotizaciones con
+    /// doble precio. El convertido se redondea a los decimales de `a`.
+    pub fn precio_dual(&self, a: Moneda, tasa: f64) -> (f64, f64) {
+        let escala = 10f64.powi(a.decimales());
+        (self.costo, (self.costo * tasa * escala).round() / escala)
+    }
+}
+
+/// Tabla de tasas de cambio entre pares de monedas.
+///
+/// Una tasa registrada de `origen` a `destino` indica cuántas unidades de
+/// `destino` equivalen a una unidad de `origen`. Si sólo existe el par
+/// inverso, se usa su recíproco.
+#[derive(Debug, Default)]
+pub struct TablaTasas {
+    tasas: HashMap<(Moneda, Moneda), f64>,
+}
+
+impl TablaTasas {
+    /// Crea una tabla vacía.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra la tasa para convertir de `origen` a `destino`. Devuelve un
+    /// error si la tasa no es positiva y finita, porque su recíproco no
+    /// serviría para el par inverso.
+    pub fn agregar(&mut self, origen: Moneda, destino: Moneda, tasa: f64) -> Result<(), OptimizadorError> {
+        if !(tasa > 0.0 && tasa.is_finite()) {
+            return Err(OptimizadorError::ParametroInvalido(format!(
+                "la tasa de {} a {} debe ser positiva, se recibió {}",
+                origen, destino, tasa
+            )));
+        }
+        self.tasas.insert((origen, destino), tasa);
+        Ok(())
+    }
+
+    /// Convierte un monto de `origen` a `destino`.
+    pub fn convertir(&self, monto: f64, origen: Moneda, destino: Moneda) -> Result<f64, OptimizadorError> {
+        if origen == destino {
+            return Ok(monto);
+        }
+        if let Some(tasa) = self.tasas.get(&(origen, destino)) {
+            return Ok(monto * tasa);
+        }
+        if let Some(tasa) = self.tasas.get(&(destino, origen)) {
+            return Ok(monto / tasa);
+        }
+        Err(OptimizadorError::TasaNoDisponible { origen, destino })
+    }
+}
+
+/// Encuentra la opción más barata normalizando el costo de cada servicio a
+/// `moneda_destino` antes de compararlo. El costo de la opción devuelta está
+/// en `moneda_destino`.
+///
+/// Un servicio con `moneda: None` no se convierte: su tarifa se toma como ya
+/// expresada en `moneda_destino`, igual que su campo documenta. Asigne la
+/// moneda a todos los servicios de un catálogo mixto.
+///
+/// Como `mejor_opcion`, descarta los servicios que no admiten el paquete y
+/// ante el mismo costo gana el primero; devuelve `Ok(None)` si ninguno lo
+/// admite. Devuelve un error si falta la tasa de algún servicio que lo admite.
+pub fn encontrar_opcion_mas_barata_en_moneda<'a>(
+    servicios: &'a [ServicioDeMensajeria],
+    paquete: &Paquete,
+    moneda_destino: Moneda,
+    tasas: &TablaTasas,
+) -> Result<Option<OpcionDeEnvio<'a>>, OptimizadorError> {
+    let mut mejor_opcion: Option<OpcionDeEnvio<'a>> = None;
+
+    for servicio in servicios.iter().filter(|servicio| servicio.admite(paquete)) {
+        let costo = servicio.calcular_costo(paquete);
+        let costo_actual = match servicio.moneda {
+            Some(moneda) => tasas.convertir(costo, moneda, moneda_destino)?,
+            None => costo,
+        };
+        if mejor_opcion.as_ref().is_none_or(|mejor| costo_actual < mejor.costo) {
+            mejor_opcion = Some(OpcionDeEnvio {
+                servicio: &servicio.nombre,
+                costo: costo_actual,
+            });
+        }
+    }
+    Ok(mejor_opcion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dimensiones, Tarifa};
+
+    fn servicio(nombre: &str, costo_base: f64, moneda: Moneda) -> ServicioDeMensajeria {
+        let mut servicio = ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base, costo_por_kg: 0.0, costo_por_volumen_cm3: 0.0, ..Default::default() },
+            ..Default::default()
+        };
+        servicio.moneda = Some(moneda);
+        servicio
+    }
+
+    fn paquete() -> Paquete {
+        Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0))
+    }
+
+    #[test]
+    fn test_servicio_eur_pierde_tras_convertir() {
+        // 9.0 EUR parece más barato que 10.0 USD, pero equivale a 10.8 USD.
+        let servicios = vec![
+            servicio("Europa Express", 9.0, Moneda::Eur),
+            servicio("Local USD", 10.0, Moneda::Usd),
+        ];
+        let mut tasas = TablaTasas::new();
+        tasas.agregar(Moneda::Eur, Moneda::Usd, 1.2).unwrap();
+
+        let mejor = encontrar_opcion_mas_barata_en_moneda(&servicios, &paquete(), Moneda::Usd, &tasas)
+            .unwrap()
+            .unwrap();
+        assert_eq!(mejor.servicio, "Local USD");
+        assert_eq!(mejor.costo, 10.0);
+    }
+
+    #[test]
+    fn test_catalogo_vacio_o_excluido_no_tiene_opcion() {
+        let tasas = TablaTasas::new();
+        assert!(matches!(encontrar_opcion_mas_barata_en_moneda(&[], &paquete(), Moneda::Usd, &tasas), Ok(None)));
+
+        // El único servicio no admite el paquete, así que tampoco se pide su tasa.
+        let mut limitado = servicio("Sobre", 2.0, Moneda::Pen);
+        limitado.peso_maximo_kg = Some(0.5);
+        let servicios = [limitado];
+        let resultado = encontrar_opcion_mas_barata_en_moneda(&servicios, &paquete(), Moneda::Usd, &tasas);
+        assert!(matches!(resultado, Ok(None)));
+    }
+
+    #[test]
+    fn test_servicio_sin_moneda_se_toma_en_moneda_destino() {
+        let mut sin_moneda = servicio("Sin moneda", 9.5, Moneda::Usd);
+        sin_moneda.moneda = None;
+        let servicios = vec![servicio("Europa Express", 9.0, Moneda::Eur), sin_moneda];
+        let mut tasas = TablaTasas::new();
+        tasas.agregar(Moneda::Eur, Moneda::Usd, 1.2).unwrap();
+
+        let mejor = encontrar_opcion_mas_barata_en_moneda(&servicios, &paquete(), Moneda::Usd, &tasas)
+            .unwrap()
+            .unwrap();
+        assert_eq!((mejor.servicio, mejor.costo), ("Sin moneda", 9.5));
+    }
+
+    #[test]
+    fn test_precio_dual_de_usd_a_pen() {
+        let opcion = OpcionDeEnvio { servicio: "Local USD", costo: 12.5 };
+        assert_eq!(opcion.precio_dual(Moneda::Pen, 3.74), (12.5, 46.75));
+        // El peso chileno no tiene decimales.
+        assert_eq!(opcion.precio_dual(Moneda::Clp, 937.3), (12.5, 11716.0));
+    }
+
+    #[test]
+    fn test_tasa_invalida_no_se_registra() {
+        let mut tasas = TablaTasas::new();
+        for tasa in [0.0, -1.2, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                tasas.agregar(Moneda::Eur, Moneda::Usd, tasa),
+                Err(OptimizadorError::ParametroInvalido(_))
+            ));
+        }
+        assert_eq!(
+            tasas.convertir(10.0, Moneda::Usd, Moneda::Eur),
+            Err(OptimizadorError::TasaNoDisponible { origen: Moneda::Usd, destino: Moneda::Eur })
+        );
+
+        tasas.agregar(Moneda::Eur, Moneda::Usd, 1.25).unwrap();
+        assert_eq!(tasas.convertir(10.0, Moneda::Usd, Moneda::Eur), Ok(8.0));
+    }
+
+    #[test]
+    fn test_tasa_faltante_es_error() {
+        let servicios = vec![servicio("Lima Envíos", 20.0, Moneda::Pen)];
+        let resultado = encontrar_opcion_mas_barata_en_moneda(&servicios, &paquete(), Moneda::Usd, &TablaTasas::new());
+        assert_eq!(
+            resultado.unwrap_err(),
+            OptimizadorError::TasaNoDisponible { origen: Moneda::Pen, destino: Moneda::Usd }
+        );
+    }
+}
@@ -0,0 +1,96 @@
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{Dimensiones, Paquete, ServicioDeMensajeria};
+
+/// Cotiza un servicio para cada peso de la lista con las mismas dimensiones.
+///
+/// Con la *feature* `rayon` los costos se calculan en paralelo. En ambos casos
+/// el resultado conserva el orden de `pesos`.
+pub fn costos_para_pesos(servicio: &ServicioDeMensajeria, dims: &Dimensiones, pesos: &[f64]) -> Vec<f64> {
+    let costo = |&peso_kg: &f64| servicio.calcular_costo(&Paquete::new(peso_kg, *dims));
+
+    #[cfg(feature = "rayon")]
+    let costos = pesos.par_iter().map(costo).collect();
+    #[cfg(not(feature = "rayon"))]
+    let costos = pesos.iter().map(costo).collect();
+
+    costos
+}
+
+/// Tamaño de lote por debajo del cual no conviene repartir el trabajo entre
+/// hilos.
+pub const UMBRAL_PARALELO: usize = 2048;
+
+/// Como `costos_para_pesos`, pero con la *feature* `rayon` reparte los pesos
+/// en bloques de `tamano_bloque` para amortizar el costo de coordinar hilos
+/// cuando cada cotización es muy barata.
+///
+/// Los lotes de menos de `UMBRAL_PARALELO` pesos se calculan en secuencia. Un
+/// `tamano_bloque` de 0 se trata como 1. El resultado conserva el orden de
+/// `pesos`.
+pub fn costos_para_pesos_por_bloques(
+    servicio: &ServicioDeMensajeria,
+    dims: &Dimensiones,
+    pesos: &[f64],
+    tamano_bloque: usize,
+) -> Vec<f64> {
+    let costo = |&peso_kg: &f64| servicio.calcular_costo(&Paquete::new(peso_kg, *dims));
+
+    #[cfg(feature = "rayon")]
+    if pesos.len() >= UMBRAL_PARALELO {
+        return pesos
+            .par_chunks(tamano_bloque.max(1))
+            .flat_map_iter(|bloque| bloque.iter().map(costo))
+            .collect();
+    }
+    #[cfg(not(feature = "rayon"))]
+    let _ = tamano_bloque;
+
+    pesos.iter().map(costo).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Tarifa, TramoPeso};
+
+    #[test]
+    fn test_costos_para_pesos_conserva_orden() {
+        let servicio = ServicioDeMensajeria {
+            nombre: "Masivo".to_string(),
+            tarifa: Tarifa {
+                costo_base: 3.0,
+                costo_por_kg: 0.8,
+                costo_por_volumen_cm3: 0.001,
+                tramos_peso: vec![TramoPeso { hasta_kg: 5.0, costo_por_kg: 1.5 }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let dims = Dimensiones::new(20.0, 15.0, 10.0);
+        let pesos: Vec<f64> = (0..1000).map(|i| (i * 37 % 1000) as f64 / 10.0).collect();
+
+        let secuencial: Vec<f64> = pesos
+            .iter()
+            .map(|&peso_kg| servicio.calcular_costo(&Paquete::new(peso_kg, dims)))
+            .collect();
+        assert_eq!(costos_para_pesos(&servicio, &dims, &pesos), secuencial);
+    }
+
+    #[test]
+    fn test_costos_por_bloques_no_dependen_del_tamano_de_bloque() {
+        let servicio = ServicioDeMensajeria {
+            nombre: "Masivo".to_string(),
+            tarifa: Tarifa { costo_base: 3.0, costo_por_kg: 0.8, costo_por_volumen_cm3: 0.001, ..Default::default() },
+            ..Default::default()
+        };
+        let dims = Dimensiones::new(20.0, 15.0, 10.0);
+        let pesos: Vec<f64> = (0..20_000).map(|i| (i * 37 % 1000) as f64 / 10.0).collect();
+
+        let esperado = costos_para_pesos(&servicio, &dims, &pesos);
+        for tamano_bloque in [1, 100, 10_000] {
+            assert_eq!(costos_para_pesos_por_bloques(&servicio, &dims, &pesos, tamano_bloque), esperado);
+        }
+    }
+}
@@ -0,0 +1,628 @@
+use crate::{Dimensiones, OpcionDeEnvio, Paquete, Recargo, ServicioDeMensajeria, mejor_opcion};
+
+/// Explica por qué cada servicio que no admite el paquete quedó excluido,
+/// devolviendo pares `(servicio, motivo)`.
+///
+/// Los servicios se ordenan de menor a mayor exceso relativo sobre sus
+/// límites, de modo que el primero indica cuánto habría que recortar el
+/// paquete para que algún servicio lo acepte. Los servicios que sí lo admiten
+/// no aparecen.
+pub fn diagnosticar_incompatibilidad(servicios: &[ServicioDeMensajeria], paquete: &Paquete) -> Vec<(String, String)> {
+    let mut diagnostico: Vec<(f64, String, String)> = servicios
+        .iter()
+        .filter_map(|servicio| {
+            let exclusiones = servicio.exclusiones(paquete);
+            let mayor_exceso = exclusiones.iter().map(|exclusion| exclusion.exceso_relativo()).reduce(f64::max)?;
+            let motivo = exclusiones.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ");
+            Some((mayor_exceso, servicio.nombre.clone(), motivo))
+        })
+        .collect();
+    diagnostico.sort_by(|a, b| a.0.total_cmp(&b.0));
+    diagnostico.into_iter().map(|(_, nombre, motivo)| (nombre, motivo)).collect()
+}
+
+/// Precio más bajo que ofrece el catálogo para cualquier paquete, útil para
+/// anunciar "desde $X".
+///
+/// Se calcula con un paquete sin peso ni volumen, pasando por el cálculo
+/// completo de cada servicio para respetar cualquier cargo fijo o recargo.
+/// Devuelve `None` si el catálogo está vacío.
+pub fn precio_minimo_teorico(servicios: &[ServicioDeMensajeria]) -> Option<f64> {
+    let vacio = Paquete::new(0.0, Dimensiones::new(0.0, 0.0, 0.0));
+    servicios
+        .iter()
+        .map(|servicio| servicio.calcular_costo(&vacio))
+        .reduce(f64::min)
+}
+
+/// Devuelve la opción más barata de cada familia de servicios, en el orden en
+/// que cada familia aparece por primera vez en el catálogo.
+///
+/// Las familias sin ningún servicio que admita el paquete no aparecen.
+pub fn mas_barata_por_familia<'a>(servicios: &'a [ServicioDeMensajeria], paquete: &Paquete) -> Vec<OpcionDeEnvio<'a>> {
+    let mut familias: Vec<&str> = Vec::new();
+    for servicio in servicios {
+        if !familias.contains(&servicio.familia_o_nombre()) {
+            familias.push(servicio.familia_o_nombre());
+        }
+    }
+
+    familias
+        .into_iter()
+        .filter_map(|familia| {
+            let de_la_familia = servicios.iter().filter(|servicio| servicio.familia_o_nombre() == familia);
+            mejor_opcion(de_la_familia, paquete)
+        })
+        .collect()
+}
+
+/// Filtra los servicios que no cobran recargos, o cuyos recargos son todos
+/// nulos, para clientes que quieren evitarlos por completo.
+pub fn servicios_sin_recargos(servicios: &[ServicioDeMensajeria]) -> Vec<&ServicioDeMensajeria> {
+    servicios
+        .iter()
+        .filter(|servicio| servicio.tarifa.recargos.iter().all(Recargo::es_nulo))
+        .collect()
+}
+
+/// Encuentra la opción más barata entre los servicios que entregan en a lo
+/// sumo `dias_maximos` días. Los servicios sin `dias_estimados` no cuentan,
+/// porque no garantizan el plazo.
+pub fn mas_barata_con_plazo<'a>(
+    servicios: &'a [ServicioDeMensajeria],
+    paquete: &Paquete,
+    dias_maximos: u32,
+) -> Option<OpcionDeEnvio<'a>> {
+    let a_tiempo = servicios
+        .iter()
+        .filter(|servicio| servicio.dias_estimados.is_some_and(|dias| dias <= dias_maximos));
+    mejor_opcion(a_tiempo, paquete)
+}
+
+/// Encuentra la opción más barata entre los servicios que aceptan el medio de
+/// pago `metodo`. Los servicios que no publican sus medios de pago no cuentan.
+pub fn mas_barata_con_pago<'a>(
+    servicios: &'a [ServicioDeMensajeria],
+    paquete: &Paquete,
+    metodo: &str,
+) -> Option<OpcionDeEnvio<'a>> {
+    let aceptan = servicios
+        .iter()
+        .filter(|servicio| servicio.metodos_pago.iter().any(|aceptado| aceptado == metodo));
+    mejor_opcion(aceptan, paquete)
+}
+
+/// Encuentra la opción más barata entre los servicios cuya `confiabilidad_pct`
+/// alcanza `min_confiabilidad`, para no elegir uno barato pero poco confiable.
+pub fn mejor_valor_confiable<'a>(
+    servicios: &'a [ServicioDeMensajeria],
+    paquete: &Paquete,
+    min_confiabilidad: f64,
+) -> Option<OpcionDeEnvio<'a>> {
+    let confiables = servicios
+        .iter()
+        .filter(|servicio| servicio.confiabilidad_pct >= min_confiabilidad);
+    mejor_opcion(confiables, paquete)
+}
+
+/// Arma una tabla de tarifas publicable: para cada límite de clase de peso en
+/// `clases` (kg), el servicio más barato y su costo con ese peso y las
+/// dimensiones `dims`, como `(peso, servicio, costo)`.
+///
+/// Las clases se devuelven en el orden recibido; se omiten las que ningún
+/// servicio admite.
+pub fn mas_barata_por_clase_peso(
+    servicios: &[ServicioDeMensajeria],
+    dims: &Dimensiones,
+    clases: &[f64],
+) -> Vec<(f64, String, f64)> {
+    clases
+        .iter()
+        .filter_map(|&peso_kg| {
+            let opcion = mejor_opcion(servicios, &Paquete::new(peso_kg, *dims))?;
+            Some((peso_kg, opcion.servicio.to_string(), opcion.costo))
+        })
+        .collect()
+}
+
+/// Encuentra la opción más barata sin considerar los servicios cuyo nombre
+/// está en `excluidos`.
+pub fn mas_barata_excluyendo<'a>(
+    servicios: &'a [ServicioDeMensajeria],
+    paquete: &Paquete,
+    excluidos: &[&str],
+) -> Option<OpcionDeEnvio<'a>> {
+    let permitidos = servicios
+        .iter()
+        .filter(|servicio| !excluidos.contains(&servicio.nombre.as_str()));
+    mejor_opcion(permitidos, paquete)
+}
+
+/// Cuánto más cuesta pasar de la opción más barata a la más rápida, como
+/// `(servicio más rápido, sobrecosto)`.
+///
+/// La más rápida es la de menos `dias_estimados` entre los servicios que
+/// admiten el paquete; ante igual plazo, la más barata. Devuelve `None` si
+/// menos de dos servicios admiten el paquete o ninguno publica su plazo.
+pub fn costo_upgrade_a_mas_rapido(servicios: &[ServicioDeMensajeria], paquete: &Paquete) -> Option<(String, f64)> {
+    let admitidos: Vec<&ServicioDeMensajeria> = servicios.iter().filter(|servicio| servicio.admite(paquete)).collect();
+    if admitidos.len() < 2 {
+        return None;
+    }
+    let mas_barata = mejor_opcion(admitidos.iter().copied(), paquete)?;
+    let dias_minimos = admitidos.iter().filter_map(|servicio| servicio.dias_estimados).min()?;
+    let mas_rapida = mejor_opcion(
+        admitidos.into_iter().filter(|servicio| servicio.dias_estimados == Some(dias_minimos)),
+        paquete,
+    )?;
+    Some((mas_rapida.servicio.to_string(), mas_rapida.costo - mas_barata.costo))
+}
+
+/// Ordena los servicios que admiten el paquete de más barato a más caro,
+/// junto a su puesto empezando en 1.
+///
+/// Cada servicio recibe un puesto distinto: ante costos iguales se conserva
+/// el orden del catálogo, de modo que el que aparece antes queda mejor
+/// ubicado, igual que en `encontrar_opcion_mas_barata`.
+pub fn rankear_con_indice<'a>(servicios: &'a [ServicioDeMensajeria], paquete: &Paquete) -> Vec<(usize, OpcionDeEnvio<'a>)> {
+    let mut opciones: Vec<OpcionDeEnvio<'a>> = servicios
+        .iter()
+        .filter(|servicio| servicio.admite(paquete))
+        .map(|servicio| OpcionDeEnvio { servicio: &servicio.nombre, costo: servicio.calcular_costo(paquete) })
+        .collect();
+    opciones.sort_by(|a, b| a.costo.total_cmp(&b.costo));
+    opciones.into_iter().enumerate().map(|(indice, opcion)| (indice + 1, opcion)).collect()
+}
+
+/// Ordena los servicios que admiten el paquete por `costo / dias_estimados`,
+/// de menor a mayor: el primero es el que menos cobra por cada día de plazo.
+///
+/// Los servicios sin `dias_estimados` no se incluyen, y la entrega en el día
+/// (cero días) cuenta como un día para no dividir por cero.
+pub fn rankear_por_eficiencia(servicios: &[ServicioDeMensajeria], paquete: &Paquete) -> Vec<(String, f64)> {
+    let mut ranking: Vec<(String, f64)> = servicios
+        .iter()
+        .filter(|servicio| servicio.admite(paquete))
+        .filter_map(|servicio| {
+            let dias = servicio.dias_estimados?.max(1);
+            Some((servicio.nombre.clone(), servicio.calcular_costo(paquete) / dias as f64))
+        })
+        .collect();
+    ranking.sort_by(|a, b| a.1.total_cmp(&b.1));
+    ranking
+}
+
+/// Elige el único servicio cuyo peor costo sobre el conjunto de paquetes es
+/// el más bajo, para contratar a un solo proveedor minimizando el riesgo.
+///
+/// Sólo compiten los servicios que admiten todos los paquetes; ante el mismo
+/// peor costo gana el primero. Devuelve `None` si no hay paquetes o ningún
+/// servicio los admite a todos.
+pub fn mejor_minimax<'a>(servicios: &'a [ServicioDeMensajeria], paquetes: &[Paquete]) -> Option<&'a ServicioDeMensajeria> {
+    let mut mejor: Option<(f64, &'a ServicioDeMensajeria)> = None;
+
+    for servicio in servicios {
+        if !paquetes.iter().all(|paquete| servicio.admite(paquete)) {
+            continue;
+        }
+        let Some(peor_costo) = paquetes.iter().map(|paquete| servicio.calcular_costo(paquete)).reduce(f64::max) else {
+            continue;
+        };
+        if mejor.is_none_or(|(actual, _)| peor_costo < actual) {
+            mejor = Some((peor_costo, servicio));
+        }
+    }
+    mejor.map(|(_, servicio)| servicio)
+}
+
+/// Elige el servicio que minimiza una métrica propia del usuario, como las
+/// emisiones o un puntaje interno, en lugar del costo.
+///
+/// Sólo compiten los servicios que admiten el paquete; ante el mismo valor
+/// gana el primero. La opción devuelta lleva el costo de envío del servicio
+/// elegido, no el valor de la métrica.
+pub fn encontrar_optima_por<'a, F>(
+    servicios: &'a [ServicioDeMensajeria],
+    paquete: &Paquete,
+    clave: F,
+) -> Option<OpcionDeEnvio<'a>>
+where
+    F: Fn(&ServicioDeMensajeria, &Paquete) -> f64,
+{
+    let mut mejor: Option<(f64, &'a ServicioDeMensajeria)> = None;
+    for servicio in servicios.iter().filter(|servicio| servicio.admite(paquete)) {
+        let valor = clave(servicio, paquete);
+        if mejor.is_none_or(|(actual, _)| valor < actual) {
+            mejor = Some((valor, servicio));
+        }
+    }
+    mejor.map(|(_, servicio)| OpcionDeEnvio { servicio: &servicio.nombre, costo: servicio.calcular_costo(paquete) })
+}
+
+/// Peso del costo en el puntaje equilibrado; el resto corresponde al plazo.
+const PESO_COSTO_EQUILIBRADO: f64 = 0.5;
+
+/// Opción más barata junto a una opción equilibrada entre costo y rapidez.
+#[derive(Debug)]
+pub struct Recomendacion<'a> {
+    pub mas_barata: OpcionDeEnvio<'a>,
+    pub equilibrada: OpcionDeEnvio<'a>,
+    /// Indica si ambas opciones son el mismo servicio.
+    pub coinciden: bool,
+}
+
+/// Recomienda la opción más barata y la equilibrada para un paquete.
+///
+/// La equilibrada minimiza un puntaje que pondera por igual el costo y los
+/// días estimados, cada uno dividido por el máximo entre los candidatos. Sólo
+/// compiten los servicios con `dias_estimados`; si ninguno lo tiene, la
+/// equilibrada es la más barata. Devuelve `None` si ningún servicio admite el
+/// paquete.
+pub fn recomendar<'a>(servicios: &'a [ServicioDeMensajeria], paquete: &Paquete) -> Option<Recomendacion<'a>> {
+    let mas_barata = mejor_opcion(servicios, paquete)?;
+
+    let candidatos: Vec<(&str, f64, f64)> = servicios
+        .iter()
+        .filter(|servicio| servicio.admite(paquete))
+        .filter_map(|servicio| {
+            let dias = servicio.dias_estimados?;
+            Some((servicio.nombre.as_str(), servicio.calcular_costo(paquete), dias as f64))
+        })
+        .collect();
+    let costo_maximo = candidatos.iter().map(|&(_, costo, _)| costo).fold(0.0, f64::max);
+    let dias_maximos = candidatos.iter().map(|&(_, _, dias)| dias).fold(0.0, f64::max);
+    let proporcion = |valor: f64, maximo: f64| if maximo > 0.0 { valor / maximo } else { 0.0 };
+
+    let mut equilibrada: Option<(f64, OpcionDeEnvio<'a>)> = None;
+    for (servicio, costo, dias) in candidatos {
+        let puntaje = PESO_COSTO_EQUILIBRADO * proporcion(costo, costo_maximo)
+            + (1.0 - PESO_COSTO_EQUILIBRADO) * proporcion(dias, dias_maximos);
+        if equilibrada.as_ref().is_none_or(|(mejor, _)| puntaje < *mejor) {
+            equilibrada = Some((puntaje, OpcionDeEnvio { servicio, costo }));
+        }
+    }
+    let equilibrada = match equilibrada {
+        Some((_, opcion)) => opcion,
+        None => OpcionDeEnvio { servicio: mas_barata.servicio, costo: mas_barata.costo },
+    };
+
+    Some(Recomendacion {
+        coinciden: mas_barata.servicio == equilibrada.servicio,
+        mas_barata,
+        equilibrada,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Tarifa, encontrar_opcion_mas_barata};
+
+    fn servicio(nombre: &str, peso_maximo_kg: f64, lado_maximo_cm: f64) -> ServicioDeMensajeria {
+        ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base: 10.0, ..Default::default() },
+            peso_maximo_kg: Some(peso_maximo_kg),
+            lado_maximo_cm: Some(lado_maximo_cm),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diagnostico_paquete_pesado_y_grande() {
+        let servicios = vec![servicio("Moto", 10.0, 50.0), servicio("Camión", 30.0, 150.0)];
+        let paquete = Paquete::new(40.0, Dimensiones::new(160.0, 40.0, 40.0));
+
+        assert_eq!(encontrar_opcion_mas_barata(&servicios, &paquete).servicio, "No disponible");
+        let diagnostico = diagnosticar_incompatibilidad(&servicios, &paquete);
+        assert_eq!(
+            diagnostico,
+            vec![
+                (
+                    "Camión".to_string(),
+                    "excede el peso máximo de 30.00 kg por 10.00 kg; excede el lado máximo de 150.00 cm por 10.00 cm"
+                        .to_string()
+                ),
+                (
+                    "Moto".to_string(),
+                    "excede el peso máximo de 10.00 kg por 30.00 kg; excede el lado máximo de 50.00 cm por 110.00 cm"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diagnostico_omite_servicios_compatibles() {
+        let servicios = vec![servicio("Moto", 10.0, 50.0), servicio("Camión", 30.0, 150.0)];
+        let paquete = Paquete::new(20.0, Dimensiones::new(40.0, 40.0, 40.0));
+        let diagnostico = diagnosticar_incompatibilidad(&servicios, &paquete);
+        assert_eq!(diagnostico.len(), 1);
+        assert_eq!(diagnostico[0].0, "Moto");
+        assert_eq!(encontrar_opcion_mas_barata(&servicios, &paquete).servicio, "Camión");
+    }
+
+    #[test]
+    fn test_precio_minimo_teorico_catalogo_de_ejemplo() {
+        let servicio = |nombre: &str, costo_base, costo_por_kg, costo_por_volumen_cm3| ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base, costo_por_kg, costo_por_volumen_cm3, ..Default::default() },
+            ..Default::default()
+        };
+        let mut servicios = vec![
+            servicio("Rappi Courier", 5.0, 1.5, 0.001),
+            servicio("Uber Paquetes", 8.0, 1.2, 0.0008),
+            servicio("DHL Express", 20.0, 1.0, 0.002),
+        ];
+        assert_eq!(precio_minimo_teorico(&servicios), Some(5.0));
+
+        servicios[0].tarifa.recargos.push(Recargo::Fragil { monto: 4.0 });
+        assert_eq!(precio_minimo_teorico(&servicios), Some(8.0));
+        assert_eq!(precio_minimo_teorico(&[]), None);
+    }
+
+    #[test]
+    fn test_mas_barata_por_familia() {
+        let servicio = |nombre: &str, familia: &str, costo_base| ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            familia: familia.to_string(),
+            tarifa: Tarifa { costo_base, costo_por_kg: 1.0, ..Default::default() },
+            ..Default::default()
+        };
+        let servicios = vec![
+            servicio("DHL Express", "DHL", 20.0),
+            servicio("Uber Flash", "Uber", 9.0),
+            servicio("DHL Economy", "DHL", 12.0),
+            servicio("Uber Paquetes", "Uber", 8.0),
+        ];
+        let paquete = Paquete::new(2.0, Dimensiones::new(10.0, 10.0, 10.0));
+
+        let ganadoras = mas_barata_por_familia(&servicios, &paquete);
+        let resumen: Vec<(&str, f64)> = ganadoras.iter().map(|opcion| (opcion.servicio, opcion.costo)).collect();
+        assert_eq!(resumen, vec![("DHL Economy", 14.0), ("Uber Paquetes", 10.0)]);
+    }
+
+    #[test]
+    fn test_servicios_sin_recargos() {
+        let con_recargos = |nombre: &str, recargos| ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { recargos, ..Default::default() },
+            ..Default::default()
+        };
+        let servicios = vec![
+            con_recargos("Combustible", vec![Recargo::Combustible { porcentaje: 8.0 }]),
+            con_recargos("Limpio", vec![]),
+            con_recargos("Frágil", vec![Recargo::TemporadaAlta { porcentaje: 0.0 }, Recargo::Fragil { monto: 3.0 }]),
+            con_recargos("Recargos en cero", vec![Recargo::Sobredimension { monto: 0.0 }]),
+        ];
+
+        let nombres: Vec<&str> = servicios_sin_recargos(&servicios).iter().map(|servicio| servicio.nombre.as_str()).collect();
+        assert_eq!(nombres, vec!["Limpio", "Recargos en cero"]);
+    }
+
+    #[test]
+    fn test_recomendar_separa_barata_y_equilibrada() {
+        let servicio = |nombre: &str, costo_base, dias| ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base, ..Default::default() },
+            dias_estimados: Some(dias),
+            ..Default::default()
+        };
+        let servicios = vec![servicio("Lento", 10.0, 7), servicio("Rápido", 12.0, 1)];
+        let paquete = Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0));
+
+        let recomendacion = recomendar(&servicios, &paquete).unwrap();
+        assert_eq!(recomendacion.mas_barata.servicio, "Lento");
+        assert_eq!(recomendacion.equilibrada.servicio, "Rápido");
+        assert!(!recomendacion.coinciden);
+    }
+
+    #[test]
+    fn test_mas_barata_con_plazo_descarta_la_lenta() {
+        let servicio = |nombre: &str, costo_base, dias| ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base, ..Default::default() },
+            dias_estimados: dias,
+            ..Default::default()
+        };
+        let servicios = vec![
+            servicio("Económico", 6.0, Some(5)),
+            servicio("Sin plazo", 4.0, None),
+            servicio("Exprés", 15.0, Some(1)),
+            servicio("Prioritario", 11.0, Some(2)),
+        ];
+        let paquete = Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0));
+
+        assert_eq!(mas_barata_con_plazo(&servicios, &paquete, 2).unwrap().servicio, "Prioritario");
+        assert!(mas_barata_con_plazo(&servicios, &paquete, 0).is_none());
+    }
+
+    #[test]
+    fn test_mas_barata_con_pago_descarta_la_que_no_acepta() {
+        let servicio = |nombre: &str, costo_base, metodos: &[&str]| ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base, ..Default::default() },
+            metodos_pago: metodos.iter().map(|metodo| metodo.to_string()).collect(),
+            ..Default::default()
+        };
+        let servicios = vec![
+            servicio("Contado", 5.0, &["efectivo", "tarjeta"]),
+            servicio("Sin datos", 3.0, &[]),
+            servicio("Corporativo", 9.0, &["tarjeta", "transferencia"]),
+        ];
+        let paquete = Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0));
+
+        let opcion = mas_barata_con_pago(&servicios, &paquete, "transferencia").unwrap();
+        assert_eq!((opcion.servicio, opcion.costo), ("Corporativo", 9.0));
+        assert_eq!(mas_barata_con_pago(&servicios, &paquete, "tarjeta").unwrap().servicio, "Contado");
+        assert!(mas_barata_con_pago(&servicios, &paquete, "cheque").is_none());
+    }
+
+    #[test]
+    fn test_mejor_valor_confiable_descarta_la_poco_confiable() {
+        let servicio = |nombre: &str, costo_base, confiabilidad_pct| ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base, ..Default::default() },
+            confiabilidad_pct,
+            ..Default::default()
+        };
+        let servicios = vec![
+            servicio("Barato", 4.0, 72.0),
+            servicio("Cumplidor", 7.0, 98.5),
+            servicio("Intermedio", 5.5, 94.0),
+        ];
+        let paquete = Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0));
+
+        assert_eq!(mejor_valor_confiable(&servicios, &paquete, 95.0).unwrap().servicio, "Cumplidor");
+        assert_eq!(mejor_valor_confiable(&servicios, &paquete, 90.0).unwrap().servicio, "Intermedio");
+        assert!(mejor_valor_confiable(&servicios, &paquete, 99.0).is_none());
+    }
+
+    #[test]
+    fn test_mas_barata_por_clase_peso() {
+        let servicio = |nombre: &str, costo_base, costo_por_kg, peso_maximo_kg| ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base, costo_por_kg, ..Default::default() },
+            peso_maximo_kg,
+            ..Default::default()
+        };
+        let servicios = vec![
+            servicio("Sobre", 2.0, 3.0, Some(1.0)),
+            servicio("Estándar", 5.0, 1.0, Some(20.0)),
+            servicio("Carga", 15.0, 0.4, None),
+        ];
+        let dims = Dimensiones::new(20.0, 20.0, 20.0);
+
+        assert_eq!(
+            mas_barata_por_clase_peso(&servicios, &dims, &[1.0, 10.0, 30.0]),
+            vec![
+                (1.0, "Sobre".to_string(), 5.0),
+                (10.0, "Estándar".to_string(), 15.0),
+                (30.0, "Carga".to_string(), 27.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mas_barata_excluyendo_pasa_a_la_siguiente() {
+        let servicio = |nombre: &str, costo_base| ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base, ..Default::default() },
+            ..Default::default()
+        };
+        let servicios = vec![servicio("Vetado", 3.0), servicio("Segundo", 4.5), servicio("Tercero", 6.0)];
+        let paquete = Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0));
+
+        let opcion = mas_barata_excluyendo(&servicios, &paquete, &["Vetado"]).unwrap();
+        assert_eq!((opcion.servicio, opcion.costo), ("Segundo", 4.5));
+        assert_eq!(mas_barata_excluyendo(&servicios, &paquete, &[]).unwrap().servicio, "Vetado");
+        assert!(mas_barata_excluyendo(&servicios, &paquete, &["Vetado", "Segundo", "Tercero"]).is_none());
+    }
+
+    #[test]
+    fn test_costo_upgrade_a_mas_rapido() {
+        let servicio = |nombre: &str, costo_base, dias| ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base, ..Default::default() },
+            dias_estimados: Some(dias),
+            ..Default::default()
+        };
+        let servicios = vec![servicio("Terrestre", 7.5, 4), servicio("Aéreo", 19.0, 1), servicio("Mixto", 12.0, 2)];
+        let paquete = Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0));
+
+        assert_eq!(costo_upgrade_a_mas_rapido(&servicios, &paquete), Some(("Aéreo".to_string(), 11.5)));
+        assert_eq!(costo_upgrade_a_mas_rapido(&servicios[..1], &paquete), None);
+    }
+
+    #[test]
+    fn test_rankear_por_eficiencia() {
+        let servicio = |nombre: &str, costo_base, dias| ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base, ..Default::default() },
+            dias_estimados: dias,
+            ..Default::default()
+        };
+        let servicios = vec![
+            servicio("Exprés", 20.0, Some(1)),
+            servicio("Terrestre", 12.0, Some(4)),
+            servicio("Mismo día", 15.0, Some(0)),
+            servicio("Sin plazo", 1.0, None),
+            servicio("Estándar", 10.0, Some(2)),
+        ];
+        let paquete = Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0));
+
+        assert_eq!(
+            rankear_por_eficiencia(&servicios, &paquete),
+            vec![
+                ("Terrestre".to_string(), 3.0),
+                ("Estándar".to_string(), 5.0),
+                ("Mismo día".to_string(), 15.0),
+                ("Exprés".to_string(), 20.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rankear_con_indice_desempata_por_orden() {
+        let servicio = |nombre: &str, costo_base| ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base, ..Default::default() },
+            ..Default::default()
+        };
+        let servicios = vec![
+            servicio("Caro", 30.0),
+            servicio("Empate A", 10.0),
+            servicio("Barato", 5.0),
+            servicio("Empate B", 10.0),
+        ];
+        let paquete = Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0));
+
+        let ranking: Vec<(usize, &str)> = rankear_con_indice(&servicios, &paquete)
+            .into_iter()
+            .map(|(puesto, opcion)| (puesto, opcion.servicio))
+            .collect();
+        assert_eq!(ranking, vec![(1, "Barato"), (2, "Empate A"), (3, "Empate B"), (4, "Caro")]);
+    }
+
+    #[test]
+    fn test_mejor_minimax_difiere_de_las_mas_baratas() {
+        let servicio = |nombre: &str, costo_base, costo_por_kg| ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base, costo_por_kg, ..Default::default() },
+            ..Default::default()
+        };
+        let servicios = vec![servicio("Por kg", 1.0, 2.0), servicio("Fijo", 12.0, 0.0), servicio("Base alta", 15.0, 0.0)];
+        let dims = Dimensiones::new(10.0, 10.0, 10.0);
+        let paquetes = vec![Paquete::new(1.0, dims), Paquete::new(2.0, dims), Paquete::new(8.0, dims)];
+
+        // "Por kg" gana cada paquete liviano, pero en el pesado llega a 17.
+        assert_eq!(encontrar_opcion_mas_barata(&servicios, &paquetes[0]).servicio, "Por kg");
+        assert_eq!(encontrar_opcion_mas_barata(&servicios, &paquetes[1]).servicio, "Por kg");
+        assert_eq!(mejor_minimax(&servicios, &paquetes).unwrap().nombre, "Fijo");
+        assert!(mejor_minimax(&servicios, &[]).is_none());
+    }
+
+    #[test]
+    fn test_encontrar_optima_por_emisiones() {
+        let servicio = |nombre: &str, costo_base| ServicioDeMensajeria {
+            nombre: nombre.to_string(),
+            tarifa: Tarifa { costo_base, ..Default::default() },
+            ..Default::default()
+        };
+        let servicios = vec![servicio("Avión", 9.0), servicio("Bicicleta", 14.0), servicio("Camión", 11.0)];
+        let paquete = Paquete::new(3.0, Dimensiones::new(10.0, 10.0, 10.0));
+        // Gramos de CO2 por kg enviado, según el medio de transporte.
+        let emisiones = |servicio: &ServicioDeMensajeria, paquete: &Paquete| {
+            let por_kg = match servicio.nombre.as_str() {
+                "Avión" => 500.0,
+                "Camión" => 60.0,
+                _ => 0.0,
+            };
+            por_kg * paquete.peso_kg
+        };
+
+        let optima = encontrar_optima_por(&servicios, &paquete, emisiones).unwrap();
+        assert_eq!(optima.servicio, "Bicicleta");
+        assert_eq!(optima.costo, 14.0);
+    }
+}
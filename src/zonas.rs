@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{OptimizadorError, Paquete, ServicioDeMensajeria};
+
+/// Factores de recargo por zona de destino, p. ej. `"rural": 1.3`.
+///
+/// Por defecto una zona que no está en la tabla es un error, para no cotizar
+/// sin recargo un destino mal escrito. Con `factor_por_defecto` se usa ese
+/// factor en su lugar.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TablaZonas {
+    pub factores: HashMap<String, f64>,
+    #[serde(default)]
+    pub factor_por_defecto: Option<f64>,
+}
+
+impl TablaZonas {
+    /// Crea una tabla vacía, sin factor por defecto.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra el factor de una zona.
+    pub fn agregar(&mut self, zona: &str, factor: f64) {
+        self.factores.insert(zona.to_string(), factor);
+    }
+
+    /// Factor de la zona, o el factor por defecto si la zona no está.
+    pub fn factor(&self, zona: &str) -> Result<f64, OptimizadorError> {
+        self.factores
+            .get(zona)
+            .copied()
+            .or(self.factor_por_defecto)
+            .ok_or_else(|| OptimizadorError::ZonaDesconocida(zona.to_string()))
+    }
+}
+
+impl ServicioDeMensajeria {
+    /// Calcula el costo de enviar el paquete a `zona`, multiplicando el costo
+    /// total por el factor de la zona en `tabla`. El piso global se aplica
+    /// después del factor.
+    pub fn calcular_costo_con_tabla_zona(
+        &self,
+        paquete: &Paquete,
+        zona: &str,
+        tabla: &TablaZonas,
+    ) -> Result<f64, OptimizadorError> {
+        Ok(self.aplicar_piso(self.costo_sin_piso(paquete, true) * tabla.factor(zona)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::carga::leer_tabla_zonas;
+    use crate::{Dimensiones, Tarifa};
+
+    fn servicio() -> ServicioDeMensajeria {
+        ServicioDeMensajeria {
+            nombre: "Nacional".to_string(),
+            tarifa: Tarifa { costo_base: 10.0, ..Default::default() },
+            ..Default::default()
+        }
+    }
+
+    fn paquete() -> Paquete {
+        Paquete::new(1.0, Dimensiones::new(10.0, 10.0, 10.0))
+    }
+
+    #[test]
+    fn test_costo_con_tabla_zona_cargada() {
+        let json = br#"{"factores": {"urbana": 1.0, "rural": 1.25}}"#;
+        let tabla = leer_tabla_zonas(&json[..]).unwrap();
+
+        assert_eq!(servicio().calcular_costo_con_tabla_zona(&paquete(), "rural", &tabla), Ok(12.5));
+        assert_eq!(
+            servicio().calcular_costo_con_tabla_zona(&paquete(), "insular", &tabla),
+            Err(OptimizadorError::ZonaDesconocida("insular".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_zona_desconocida_con_factor_por_defecto() {
+        let mut tabla = TablaZonas::new();
+        tabla.agregar("urbana", 1.0);
+        tabla.factor_por_defecto = Some(1.5);
+        assert_eq!(servicio().calcular_costo_con_tabla_zona(&paquete(), "insular", &tabla), Ok(15.0));
+    }
+}